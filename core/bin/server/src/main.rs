@@ -146,12 +146,10 @@ async fn main() -> anyhow::Result<()> {
     // It's a `ServerCommand::Launch`, perform the usual routine.
     vlog::info!("Running the zkSync server");
 
-    run_server(&opt.components).await;
-
-    Ok(())
+    run_server(&opt.components).await
 }
 
-async fn run_server(components: &ComponentsToRun) {
+async fn run_server(components: &ComponentsToRun) -> anyhow::Result<()> {
     let connection_pool = ConnectionPool::new(None);
     let read_only_connection_pool = ConnectionPool::new_readonly_pool(None);
     let (stop_signal_sender, mut stop_signal_receiver) = mpsc::channel(256);
@@ -160,11 +158,14 @@ async fn run_server(components: &ComponentsToRun) {
 
     if components.0.contains(&Component::Web3Api) {
         // Run web3 api
-        tasks.push(zksync_api::api_server::web3::start_rpc_server(
-            connection_pool.clone(),
-            &Web3Config::from_env(),
-            &TokenConfig::from_env(),
-        ));
+        tasks.push(
+            zksync_api::api_server::web3::start_rpc_server(
+                connection_pool.clone(),
+                &Web3Config::from_env(),
+                &TokenConfig::from_env(),
+            )
+            .await?,
+        );
     }
 
     if components.0.contains(&Component::Fetchers) {
@@ -341,6 +342,8 @@ async fn run_server(components: &ComponentsToRun) {
             vlog::warn!("Stop signal received, shutting down");
         }
     };
+
+    Ok(())
 }
 
 pub fn run_forced_exit(connection_pool: ConnectionPool) -> Vec<JoinHandle<()>> {
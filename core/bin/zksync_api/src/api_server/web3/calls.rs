@@ -1,242 +1,1794 @@
 // Built-in uses
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
-use std::path::PathBuf;
+use std::path::Path;
 use std::str::FromStr;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 // External uses
-use ethabi::{encode, Contract, Function, Token as AbiToken};
-use jsonrpc_core::{Error, ErrorCode, Result};
+use anyhow::format_err;
+use ethabi::{encode, Contract, Function, ParamType, Token as AbiToken};
+use jsonrpc_core::{Error, ErrorCode, Result, Value};
+use num::BigUint;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
 use tiny_keccak::keccak256;
+use tokio::sync::RwLock;
 // Workspace uses
+use zksync_config::configs::api::{
+    AllowancePolicy, CidVersion, Multihash, NftTotalSupply, TokenConfig, TokenUriFormat, Web3Config,
+};
+use zksync_crypto::params::{ETH_TOKEN_ID, MIN_NFT_TOKEN_ID, NFT_TOKEN_ID_VAL};
 use zksync_storage::StorageProcessor;
 use zksync_token_db_cache::TokenDBCache;
-use zksync_types::{TokenId, TokenKind, NFT};
+use zksync_types::{
+    tokens::{get_genesis_token_list, TokenInfo},
+    BlockNumber, TokenId, TokenKind, NFT,
+};
 
 // Local uses
+#[cfg(test)]
+use super::error::Base58Error;
 use super::{
-    converter::u256_from_biguint,
-    types::{H160, U256},
+    abi_dir_from_env, check_abi_dir,
+    converter::{resolve_block_number, try_u256_from_biguint},
+    error::CallError,
+    load_abi,
+    provider::{NftProvider, TokenProvider},
+    types::{BlockNumber as BlockTag, H160, H256, U256},
+    ERC1155_ABI, ERC20_ABI, MULTICALL3_ABI, MULTICALL3_ADDRESS, NFT_FACTORY_ABI,
     NFT_FACTORY_ADDRESS, ZKSYNC_PROXY_ADDRESS,
 };
+use crate::utils::shared_lru_cache::AsyncLruCache;
+
+pub type Selector = [u8; 4];
+
+/// Outcome of the transfer simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferSimulation {
+    /// The sender owns enough tokens.
+    Ok,
+    /// The sender doesn't have enough tokens, or doesn't own the NFT.
+    InsufficientBalance,
+    /// There is no token with such address.
+    UnknownToken,
+}
+
+/// Kind of the emulated contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContractKind {
+    Erc20,
+    /// NFT factory and zkSync proxies.
+    Nft,
+    /// ERC1155 functions of the NFT contracts.
+    Erc1155,
+    Multicall3,
+}
+
+/// ERC721 metadata JSON, the fields are serialized in the order of declaration.
+#[derive(Debug, Serialize)]
+struct Erc721Metadata {
+    name: String,
+    description: String,
+    image: String,
+    attributes: Vec<Erc721Attribute>,
+}
+
+#[derive(Debug, Serialize)]
+struct Erc721Attribute {
+    trait_type: &'static str,
+    value: Value,
+}
 
-type Selector = [u8; 4];
+/// How the call was answered, see `CallsHelper::execute_outcome`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecuteOutcome {
+    /// Output of the emulated function, empty for the calls without data.
+    Answered(Vec<u8>),
+    /// The address isn't an emulated contract or the token isn't allowed.
+    NotAContract,
+    /// The emulated contract has no function with this selector.
+    UnknownSelector(Selector),
+    /// The address isn't a known ERC20 token.
+    MissingToken,
+}
+
+impl ExecuteOutcome {
+    /// Converts the outcome to the `eth_call` result: unknown selectors are invalid params,
+    /// other calls that weren't answered return nothing.
+    pub fn into_result(self) -> Result<Vec<u8>> {
+        match self {
+            Self::Answered(output) => Ok(output),
+            Self::NotAContract | Self::MissingToken => Ok(Vec::new()),
+            Self::UnknownSelector(selector) => Err(Error::invalid_params(format!(
+                "unknown function selector 0x{}",
+                hex::encode(selector)
+            ))),
+        }
+    }
+
+    /// Label of the outcome in the metrics.
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Answered(_) => "answered",
+            Self::NotAContract => "not_a_contract",
+            Self::UnknownSelector(_) => "unknown_selector",
+            Self::MissingToken => "missing_token",
+        }
+    }
+}
+
+/// How `execute` classifies the call, see `CallsHelper::explain`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallExplanation {
+    /// `None` if the address is not an emulated contract.
+    pub contract: Option<ContractKind>,
+    /// Name of the matched function or "unknown selector".
+    pub function: String,
+    /// Decoded arguments, empty if they can't be decoded.
+    pub params: Vec<String>,
+}
 
+/// Where `execute` routes the call, see `CallsHelper::classify`.
+#[derive(Debug, Clone)]
+pub(super) enum Route {
+    /// The NFT factory or the zkSync proxy, ERC721 and ERC1155 calls.
+    Proxy,
+    /// The allowed ERC20 token.
+    Erc20(TokenMetadata),
+    /// The NFT token, its functions are served by the NFT factory, so calls return nothing.
+    NftEmpty,
+    /// The zero address, the token that isn't allowed or doesn't exist. Calls return nothing.
+    Unknown,
+}
+
+/// ERC20 token properties that never change once the token is added.
+#[derive(Debug, Clone)]
+pub(super) struct TokenMetadata {
+    id: TokenId,
+    name: String,
+    symbol: String,
+    decimals: u8,
+}
+
+/// Arguments of the `CallsHelper::self_test` calls, see `CallsHelper::fixture_argument`.
+#[derive(Debug)]
+struct SelfTestFixtures {
+    erc20_address: Option<H160>,
+    nft: Option<NFT>,
+    /// Owner of the NFT, the zero address if there is no NFT.
+    owner: H160,
+}
+
+/// See `CallsHelper::shared`.
+static SHARED_CALLS_HELPER: OnceCell<CallsHelper> = OnceCell::new();
+
+/// Cloning is cheap: the ABI tables are shared and the caches are shared as well.
 #[derive(Debug, Clone)]
 pub struct CallsHelper {
-    erc20: HashMap<Selector, Function>,
-    nft_factory: HashMap<Selector, Function>,
+    erc20: Arc<HashMap<Selector, Function>>,
+    nft_factory: Arc<HashMap<Selector, Function>>,
+    /// ERC1155 functions served by the same contracts as the NFT ones.
+    erc1155: Arc<HashMap<Selector, Function>>,
+    multicall3: Arc<HashMap<Selector, Function>>,
     tokens: TokenDBCache,
-    zksync_proxy_address: H160,
+    token_metadata: Arc<RwLock<HashMap<H160, TokenMetadata>>>,
+    /// Bumped on every invalidation, so the metadata loaded before it isn't cached afterwards.
+    token_metadata_generation: Arc<AtomicU64>,
+    /// Encoded outputs of the `IMMUTABLE_NFT_FUNCTIONS` calls at the latest state.
+    nft_outputs: AsyncLruCache<(TokenId, Selector), Vec<u8>>,
+    /// The first address is reported by `getApproved` if `get_approved_proxy` is set.
+    zksync_proxy_addresses: Vec<H160>,
+    get_approved_proxy: bool,
+    /// If set, other ERC20 tokens are not emulated even if they exist.
+    allowed_tokens: Option<HashSet<H160>>,
+    /// Genesis tokens, their `decimals` and `symbol` never change, so the storage isn't accessed.
+    system_tokens: Arc<HashMap<H160, TokenInfo>>,
     nft_factory_address: H160,
+    multicall3_address: H160,
+    cid_version: CidVersion,
+    content_multihash: Multihash,
+    token_uri_prefix: String,
+    token_uri_format: TokenUriFormat,
+    /// Used instead of the IPFS CID for the NFTs without a content hash.
+    base_token_uri: Option<String>,
+    collection_name: String,
+    collection_symbol: String,
+    total_supply: NftTotalSupply,
+    /// Used in the EIP-712 domain of the tokens.
+    chain_id: u64,
+    /// `{address}` and `{symbol}` are replaced by the ones of the token.
+    logo_uri_pattern: Option<String>,
+    /// Added to the ERC20 `symbol` and `name` of the tokens without an override.
+    symbol_prefix: String,
+    symbol_suffix: String,
+    symbol_overrides: HashMap<H160, String>,
+    allowance_policy: AllowancePolicy,
+    /// Calls that would scan more storage rows fail with "out of gas".
+    max_scanned_rows: u32,
+    /// Larger call data is rejected before decoding, since the encoded array lengths
+    /// define the allocations.
+    max_call_data_size: usize,
+    /// NFT IDs above it are never looked up in the storage.
+    max_nft_token_id: u32,
 }
 
 impl CallsHelper {
     const SHA256_MULTI_HASH: [u8; 2] = [18, 32]; // 0x1220
+    const KECCAK256_MULTI_HASH: [u8; 2] = [27, 32]; // 0x1b20
+    const BLAKE2B256_MULTI_HASH: [u8; 4] = [160, 228, 2, 32]; // varint(0xb220) and 0x20
+    const BLAKE2S256_MULTI_HASH: [u8; 4] = [224, 228, 2, 32]; // varint(0xb260) and 0x20
     const ALPHABET: &'static str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    /// CIDv1 version and `dag-pb` codec, the same codec that is implied by CIDv0.
+    const CID_V1_DAG_PB_PREFIX: [u8; 2] = [0x01, 0x70];
+    const BASE32_ALPHABET: &'static str = "abcdefghijklmnopqrstuvwxyz234567";
+    /// Max number of IDs returned by a single `getOwnerTokens` call.
+    const MAX_OWNER_TOKENS_LIMIT: u32 = 100;
+    /// Slot of the balances mapping in the canonical ERC20 layout, e.g. OpenZeppelin `_balances`.
+    const ERC20_BALANCES_SLOT: u64 = 0;
+    /// Max number of tokens queried by a single `balancesOf` call.
+    const MAX_BALANCES_TOKENS: usize = 100;
+    /// Max number of the cached `IMMUTABLE_NFT_FUNCTIONS` outputs.
+    const NFT_OUTPUTS_CACHE_SIZE: usize = 100_000;
+    /// Values of the `uint8` enum returned by `getContractType`.
+    const CONTRACT_TYPE_UNKNOWN: u8 = 0;
+    const CONTRACT_TYPE_ERC20: u8 = 1;
+    const CONTRACT_TYPE_NFT: u8 = 2;
+    /// Interface IDs reported by `supportsInterface`: ERC165, ERC721 and ERC721Metadata.
+    const SUPPORTED_INTERFACES: [Selector; 3] = [
+        [0x01, 0xff, 0xc9, 0xa7],
+        [0x80, 0xac, 0x58, 0xcd],
+        [0x5b, 0x5e, 0x13, 0x9f],
+    ];
+
+    /// EIP-1167 minimal proxy bytecode, the implementation address goes in between.
+    const EIP1167_PREFIX: [u8; 10] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+    const EIP1167_SUFFIX: [u8; 15] = [
+        0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91, 0x60, 0x2b, 0x57, 0xfd, 0x5b, 0xf3,
+    ];
 
-    fn revert_error(message: &str) -> Error {
+    /// Functions handled by `call_function`, `call_erc1155_function` and `execute_multicall`,
+    /// must be updated together with their match arms.
+    const ERC20_FUNCTIONS: [&'static str; 13] = [
+        "name",
+        "symbol",
+        "decimals",
+        "totalSupply",
+        "allowance",
+        "nonces",
+        "DOMAIN_SEPARATOR",
+        "PERMIT_TYPEHASH",
+        "balanceOf",
+        "transfer",
+        "transferFrom",
+        "increaseAllowance",
+        "decreaseAllowance",
+    ];
+    const NFT_FUNCTIONS: [&'static str; 26] = [
+        "name",
+        "symbol",
+        "creatorId",
+        "creatorAddress",
+        "serialId",
+        "contentHash",
+        "contentHashCid",
+        "tokenURI",
+        "getTokenAddress",
+        "getTokenId",
+        "balanceOf",
+        "ownerOf",
+        "getApproved",
+        "isApprovedForAll",
+        "approve",
+        "setApprovalForAll",
+        "supportsInterface",
+        "totalSupply",
+        "tokenByIndex",
+        "tokenOfOwnerByIndex",
+        "getOwnerTokens",
+        "getContractType",
+        "tokenInfo",
+        "getCreator",
+        "getNftSummary",
+        "balancesOf",
+    ];
+    const ERC1155_FUNCTIONS: [&'static str; 2] = ["balanceOf", "balanceOfBatch"];
+    const MULTICALL3_FUNCTIONS: [&'static str; 1] = ["aggregate3"];
+    /// NFT functions depending only on the minted NFT, their outputs never change.
+    /// Ownership and approvals do change, so `ownerOf`, `balanceOf` and `getApproved` aren't here.
+    const IMMUTABLE_NFT_FUNCTIONS: [&'static str; 7] = [
+        "creatorId",
+        "creatorAddress",
+        "getCreator",
+        "getNftSummary",
+        "serialId",
+        "contentHash",
+        "tokenURI",
+    ];
+    /// Functions that always revert, zkSync state can only be changed by transactions.
+    const REVERTING_FUNCTIONS: [&'static str; 6] = [
+        "transfer",
+        "transferFrom",
+        "increaseAllowance",
+        "decreaseAllowance",
+        "approve",
+        "setApprovalForAll",
+    ];
+    /// Files that must be present in the ABI directory.
+    const ABI_FILES: [&'static str; 4] = [
+        "ERC20.json",
+        "NFTFactory.json",
+        "ERC1155.json",
+        "Multicall3.json",
+    ];
+
+    /// EIP-2612 permit and EIP-712 domain types.
+    const PERMIT_TYPE: &'static str =
+        "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)";
+    const EIP712_DOMAIN_TYPE: &'static str =
+        "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+    /// Selector of the standard `Error(string)` revert payload.
+    const REVERT_SELECTOR: Selector = [0x08, 0xc3, 0x79, 0xa0];
+
+    /// Builds the `Error(string)` payload, the same one that Solidity contracts return on revert.
+    fn revert_data(reason: &str) -> Vec<u8> {
+        let mut data = Self::REVERT_SELECTOR.to_vec();
+        data.extend(encode(&[AbiToken::String(reason.to_string())]));
+        data
+    }
+
+    fn revert_error(reason: &str) -> Error {
         Error {
             code: ErrorCode::ServerError(3),
-            message: message.to_string(),
+            message: format!("execution reverted: {}", reason),
+            data: Some(Value::String(format!(
+                "0x{}",
+                hex::encode(Self::revert_data(reason))
+            ))),
+        }
+    }
+
+    /// Reported for the functions that are present in the ABI but have no emulation,
+    /// which means that the ABI files and the code went out of sync.
+    fn unimplemented_function_error(function: &Function) -> Error {
+        vlog::error!("Function {} is not implemented", function.name);
+        CallError::UnsupportedFunction(function.name.clone()).into()
+    }
+
+    /// Same error as the one returned by the Ethereum nodes when the call exceeds the gas limit.
+    fn out_of_gas_error() -> Error {
+        Error {
+            code: ErrorCode::ServerError(-32000),
+            message: "out of gas".to_string(),
             data: None,
         }
     }
 
-    fn function_by_selector(functions: Vec<Function>) -> HashMap<Selector, Function> {
-        functions
-            .into_iter()
-            .map(|f| {
-                let inputs = f
-                    .inputs
-                    .iter()
-                    .map(|p| p.kind.to_string())
-                    .collect::<Vec<_>>()
-                    .join(",");
-                let signature = format!("{}({})", f.name, inputs);
-                let selector: Selector = keccak256(signature.as_bytes())[0..4].try_into().unwrap();
-                (selector, f)
-            })
-            .collect()
+    /// Fails if the call would scan more storage rows than allowed, `rows` is the offset
+    /// of the requested item.
+    fn check_scanned_rows(&self, rows: U256) -> Result<()> {
+        if rows >= U256::from(self.max_scanned_rows) {
+            return Err(Self::out_of_gas_error());
+        }
+        Ok(())
     }
 
-    pub fn new(invalidate_token_cache_period: Duration) -> Self {
-        let mut path = PathBuf::new();
-        path.push(std::env::var("ZKSYNC_HOME").unwrap_or_else(|_| "/".to_string()));
-        path.push("etc/web3-abi");
-        let erc20_abi = std::fs::File::open(path.join("ERC20.json")).unwrap();
-        let erc20_functions = Contract::load(erc20_abi)
-            .unwrap()
-            .functions
+    /// Returns the selector of the function signature, e.g. `balanceOf(address)`,
+    /// the first 4 bytes of its keccak256 hash.
+    pub fn selector_for(signature: &str) -> Selector {
+        keccak256(signature.as_bytes())[0..4].try_into().unwrap()
+    }
+
+    /// Builds the dispatch table, fails if two functions share the same selector,
+    /// since one of them would be unreachable otherwise.
+    pub(super) fn function_by_selector(
+        functions: Vec<Function>,
+    ) -> anyhow::Result<HashMap<Selector, Function>> {
+        let mut function_by_selector = HashMap::with_capacity(functions.len());
+        for f in functions {
+            let inputs = f
+                .inputs
+                .iter()
+                .map(|p| p.kind.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            let signature = format!("{}({})", f.name, inputs);
+            let selector = Self::selector_for(&signature);
+            if function_by_selector.insert(selector, f).is_some() {
+                anyhow::bail!(
+                    "Function {} collides with another function by selector 0x{}",
+                    signature,
+                    hex::encode(selector)
+                );
+            }
+        }
+        Ok(function_by_selector)
+    }
+
+    /// Returns the sorted names of the ABI functions without a match arm,
+    /// their calls fail with "function is not implemented".
+    fn unimplemented_functions(
+        functions: &HashMap<Selector, Function>,
+        implemented: &[&str],
+    ) -> Vec<String> {
+        let mut names: Vec<String> = functions
             .values()
-            .flatten()
-            .cloned()
+            .filter(|function| !implemented.contains(&function.name.as_str()))
+            .map(|function| function.name.clone())
             .collect();
-        let erc20_function_by_selector = Self::function_by_selector(erc20_functions);
+        names.sort();
+        names.dedup();
+        names
+    }
 
-        let nft_factory_abi = std::fs::File::open(path.join("NFTFactory.json")).unwrap();
-        let nft_factory_functions = Contract::load(nft_factory_abi)
-            .unwrap()
-            .functions
-            .values()
-            .flatten()
-            .cloned()
+    pub(super) fn contract_functions(contract: Contract) -> Vec<Function> {
+        contract.functions.into_values().flatten().collect()
+    }
+
+    /// ABIs are loaded from `ZKSYNC_WEB3_ABI_DIR` if it's set, the embedded ones are used otherwise.
+    pub fn new(
+        config: &Web3Config,
+        invalidate_token_cache_period: Duration,
+    ) -> anyhow::Result<Self> {
+        Self::with_abi_dir(
+            config,
+            invalidate_token_cache_period,
+            abi_dir_from_env().as_deref(),
+        )
+    }
+
+    /// Same as `new`, but the ABIs are loaded from `abi_dir` if it's set. The directory must
+    /// contain all of the `ABI_FILES`.
+    pub fn with_abi_dir(
+        config: &Web3Config,
+        invalidate_token_cache_period: Duration,
+        abi_dir: Option<&Path>,
+    ) -> anyhow::Result<Self> {
+        if let Some(dir) = abi_dir {
+            check_abi_dir(dir, &Self::ABI_FILES)?;
+        }
+        let erc20_functions = Self::contract_functions(load_abi(abi_dir, "ERC20.json", ERC20_ABI)?);
+        let erc20_function_by_selector = Self::function_by_selector(erc20_functions)?;
+
+        let nft_factory_functions =
+            Self::contract_functions(load_abi(abi_dir, "NFTFactory.json", NFT_FACTORY_ABI)?);
+        let nft_factory_function_by_selector = Self::function_by_selector(nft_factory_functions)?;
+
+        let erc1155_functions =
+            Self::contract_functions(load_abi(abi_dir, "ERC1155.json", ERC1155_ABI)?);
+        let erc1155_function_by_selector = Self::function_by_selector(erc1155_functions)?;
+
+        let multicall3_functions =
+            Self::contract_functions(load_abi(abi_dir, "Multicall3.json", MULTICALL3_ABI)?);
+        let multicall3_function_by_selector = Self::function_by_selector(multicall3_functions)?;
+
+        let tables: [(&str, &HashMap<Selector, Function>, &[&str]); 4] = [
+            (
+                "ERC20",
+                &erc20_function_by_selector,
+                &Self::ERC20_FUNCTIONS[..],
+            ),
+            (
+                "NFTFactory",
+                &nft_factory_function_by_selector,
+                &Self::NFT_FUNCTIONS[..],
+            ),
+            (
+                "ERC1155",
+                &erc1155_function_by_selector,
+                &Self::ERC1155_FUNCTIONS[..],
+            ),
+            (
+                "Multicall3",
+                &multicall3_function_by_selector,
+                &Self::MULTICALL3_FUNCTIONS[..],
+            ),
+        ];
+        let unimplemented: Vec<String> = tables
+            .iter()
+            .flat_map(|(contract, functions, implemented)| {
+                Self::unimplemented_functions(functions, implemented)
+                    .into_iter()
+                    .map(move |name| format!("{}.{}", contract, name))
+            })
             .collect();
-        let nft_factory_function_by_selector = Self::function_by_selector(nft_factory_functions);
+        if !unimplemented.is_empty() {
+            anyhow::ensure!(
+                !config.call_strict_abi,
+                "ABI functions without an emulation: {}",
+                unimplemented.join(", ")
+            );
+        }
+        // Each function is logged separately, so the drift can be found by the function name.
+        for name in &unimplemented {
+            vlog::warn!("ABI function {} has no emulation, its calls fail", name);
+        }
+
+        anyhow::ensure!(
+            config.nft_token_uri_prefix.ends_with('/'),
+            "NFT token URI prefix must end with '/' or '://', got {}",
+            config.nft_token_uri_prefix
+        );
+        anyhow::ensure!(
+            config.nft_cid_version == CidVersion::V1
+                || config.nft_content_multihash == Multihash::Sha256,
+            "CIDv0 implies SHA256 content hashes, use CIDv1 for {:?}",
+            config.nft_content_multihash
+        );
+        if let Some(pattern) = &config.erc20_logo_uri_pattern {
+            anyhow::ensure!(
+                pattern.contains("{address}") || pattern.contains("{symbol}"),
+                "ERC20 logo URI pattern must contain {{address}} or {{symbol}}, got {}",
+                pattern
+            );
+        }
+
+        let zksync_proxy_addresses = if config.zksync_proxy_addresses.is_empty() {
+            let zksync_proxy_address = H160::from_str(ZKSYNC_PROXY_ADDRESS)
+                .map_err(|err| format_err!("Invalid zkSync proxy address: {}", err))?;
+            vec![zksync_proxy_address]
+        } else {
+            config.zksync_proxy_addresses.clone()
+        };
+        let system_tokens = match &config.system_tokens_network {
+            Some(network) => get_genesis_token_list(network)
+                .map_err(|err| format_err!("Cannot load {} genesis tokens: {}", network, err))?
+                .into_iter()
+                .map(|token| (token.address, token))
+                .collect(),
+            None => HashMap::new(),
+        };
+        let symbol_overrides = config
+            .erc20_symbol_overrides
+            .iter()
+            .flatten()
+            .map(|entry| {
+                let (address, symbol) = entry.split_once(':').ok_or_else(|| {
+                    format_err!(
+                        "Invalid ERC20 symbol override {}: expected address:symbol",
+                        entry
+                    )
+                })?;
+                let address = H160::from_str(address.trim_start_matches("0x")).map_err(|err| {
+                    format_err!("Invalid ERC20 symbol override {}: {}", entry, err)
+                })?;
+                Ok((address, symbol.to_string()))
+            })
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+        let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS)
+            .map_err(|err| format_err!("Invalid NFT factory address: {}", err))?;
+        let multicall3_address = H160::from_str(MULTICALL3_ADDRESS)
+            .map_err(|err| format_err!("Invalid Multicall3 address: {}", err))?;
 
-        Self {
-            erc20: erc20_function_by_selector,
-            nft_factory: nft_factory_function_by_selector,
+        Ok(Self {
+            erc20: Arc::new(erc20_function_by_selector),
+            nft_factory: Arc::new(nft_factory_function_by_selector),
+            erc1155: Arc::new(erc1155_function_by_selector),
+            multicall3: Arc::new(multicall3_function_by_selector),
             tokens: TokenDBCache::new(invalidate_token_cache_period),
-            zksync_proxy_address: H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap(),
-            nft_factory_address: H160::from_str(NFT_FACTORY_ADDRESS).unwrap(),
+            token_metadata: Default::default(),
+            token_metadata_generation: Default::default(),
+            nft_outputs: AsyncLruCache::new(Self::NFT_OUTPUTS_CACHE_SIZE),
+            zksync_proxy_addresses,
+            get_approved_proxy: config.nft_get_approved_proxy,
+            allowed_tokens: config
+                .call_allowed_tokens
+                .as_ref()
+                .map(|tokens| tokens.iter().copied().collect()),
+            system_tokens: Arc::new(system_tokens),
+            nft_factory_address,
+            multicall3_address,
+            cid_version: config.nft_cid_version,
+            content_multihash: config.nft_content_multihash,
+            token_uri_prefix: config.nft_token_uri_prefix.clone(),
+            token_uri_format: config.nft_token_uri_format,
+            base_token_uri: config.nft_base_token_uri.clone(),
+            collection_name: config.nft_collection_name.clone(),
+            collection_symbol: config.nft_collection_symbol.clone(),
+            total_supply: config.nft_total_supply,
+            chain_id: config.chain_id,
+            logo_uri_pattern: config.erc20_logo_uri_pattern.clone(),
+            symbol_prefix: config.erc20_symbol_prefix.clone().unwrap_or_default(),
+            symbol_suffix: config.erc20_symbol_suffix.clone().unwrap_or_default(),
+            symbol_overrides,
+            allowance_policy: config.erc20_allowance_policy,
+            max_scanned_rows: config.call_max_scanned_rows,
+            max_call_data_size: config.call_max_data_size,
+            max_nft_token_id: config.nft_max_token_id.unwrap_or(u32::MAX),
+        })
+    }
+
+    /// Returns the helper configured from the environment, it's created on the first call,
+    /// so the ABIs are parsed once per process. The web3 server is configured explicitly,
+    /// so it creates its own helper.
+    pub fn shared() -> anyhow::Result<&'static Self> {
+        SHARED_CALLS_HELPER.get_or_try_init(|| {
+            let invalidate_token_cache_period =
+                TokenConfig::from_env().invalidate_token_cache_period();
+            Self::new(&Web3Config::from_env(), invalidate_token_cache_period)
+                .map_err(|err| format_err!("Cannot create the shared CallsHelper: {}", err))
+        })
+    }
+
+    /// Checks that both helpers use the same ABI tables rather than copies of them.
+    #[cfg(test)]
+    pub(super) fn shares_abi_tables(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.erc20, &other.erc20)
+            && Arc::ptr_eq(&self.nft_factory, &other.nft_factory)
+            && Arc::ptr_eq(&self.erc1155, &other.erc1155)
+            && Arc::ptr_eq(&self.multicall3, &other.multicall3)
+    }
+
+    /// Adds NFT functions to the ABI table of this helper only.
+    #[cfg(test)]
+    pub(super) fn extend_nft_functions(&mut self, functions: Vec<Function>) -> anyhow::Result<()> {
+        let function_by_selector = Self::function_by_selector(functions)?;
+        Arc::make_mut(&mut self.nft_factory).extend(function_by_selector);
+        Ok(())
+    }
+
+    /// Finds the function called by the calldata of the emulated contract.
+    fn function_for_call<'a>(
+        functions: &'a HashMap<Selector, Function>,
+        data: &[u8],
+    ) -> Result<&'a Function> {
+        let selector: Selector = if data.len() >= 4 {
+            data[0..4].try_into().unwrap()
+        } else {
+            return Err(Error::invalid_params(
+                "calldata too short to contain a selector",
+            ));
+        };
+        functions.get(&selector).ok_or_else(|| {
+            Error::invalid_params(format!(
+                "unknown function selector 0x{}",
+                hex::encode(selector)
+            ))
+        })
+    }
+
+    /// Decodes the arguments that follow the selector in the calldata.
+    fn decode_params(function: &Function, data: &[u8]) -> Result<Vec<AbiToken>> {
+        let arguments = &data[4..];
+        function.decode_input(arguments).map_err(|_| {
+            Error::invalid_params(format!(
+                "cannot decode arguments of {}: {} bytes received",
+                function.name,
+                arguments.len()
+            ))
+        })
+    }
+
+    /// Calls to the NFT factory and to any of the zkSync proxies are served by the NFT functions.
+    fn is_nft_contract(&self, to: H160) -> bool {
+        to == self.nft_factory_address || self.zksync_proxy_addresses.contains(&to)
+    }
+
+    /// Returns the functions that are both present in the ABI and emulated, sorted by
+    /// the contract kind and the name.
+    pub fn supported_functions(&self) -> Vec<(ContractKind, String)> {
+        let tables: [(ContractKind, &HashMap<Selector, Function>, &[&str]); 4] = [
+            (
+                ContractKind::Erc20,
+                &*self.erc20,
+                &Self::ERC20_FUNCTIONS[..],
+            ),
+            (
+                ContractKind::Nft,
+                &*self.nft_factory,
+                &Self::NFT_FUNCTIONS[..],
+            ),
+            (
+                ContractKind::Erc1155,
+                &*self.erc1155,
+                &Self::ERC1155_FUNCTIONS[..],
+            ),
+            (
+                ContractKind::Multicall3,
+                &*self.multicall3,
+                &Self::MULTICALL3_FUNCTIONS[..],
+            ),
+        ];
+        // Overloaded functions share the name, so they are reported once.
+        let mut functions: Vec<(ContractKind, String)> = tables
+            .iter()
+            .flat_map(|(kind, table, implemented)| {
+                table
+                    .values()
+                    .filter(move |function| implemented.contains(&function.name.as_str()))
+                    .map(move |function| (*kind, function.name.clone()))
+            })
+            .collect();
+        functions.sort();
+        functions.dedup();
+        functions
+    }
+
+    /// Calls every ABI function of the emulated contracts with the fixture arguments and checks
+    /// that the output is decoded by the ABI. The state-changing functions (see
+    /// `REVERTING_FUNCTIONS`) must revert, any other revert means that the ABIs and the emulation
+    /// went out of sync, and so does any error.
+    /// The fixtures are the first ERC20 token and the first minted NFT with its owner. The functions
+    /// of the contracts without fixtures (e.g. there are no NFTs yet) aren't checked.
+    pub async fn self_test(&self, storage: &mut StorageProcessor<'_>) -> Result<()> {
+        let verified_block = self.block_number(storage, None, None).await?;
+        let erc20_address = match &self.allowed_tokens {
+            Some(tokens) => tokens.iter().min().copied(),
+            None => storage
+                .tokens_schema()
+                .load_tokens_asc(TokenId(1), Some(1))
+                .await
+                .map_err(|_| CallError::StorageUnavailable)?
+                .first()
+                .map(|token| token.address),
+        };
+        let nft_id = storage
+            .tokens_schema()
+            .get_minted_nft_by_index(verified_block, 0)
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
+        let nft = self.get_nft(storage, nft_id).await?;
+        let nft_owner = match &nft {
+            Some(nft) => self.nft_owner(storage, nft.id, verified_block).await?,
+            None => None,
+        };
+        let fixtures = SelfTestFixtures {
+            erc20_address,
+            nft,
+            owner: nft_owner.unwrap_or_default(),
+        };
+
+        let mut contracts = vec![("Multicall3", self.multicall3_address, &*self.multicall3)];
+        // Calls for the nonexistent NFT revert, as well as the ones for an unowned NFT.
+        if nft_owner.is_some() {
+            contracts.insert(0, ("ERC1155", self.nft_factory_address, &*self.erc1155));
+            contracts.insert(
+                0,
+                ("NFTFactory", self.nft_factory_address, &*self.nft_factory),
+            );
+        } else {
+            vlog::warn!("There are no owned NFTs, their functions aren't self-tested");
+        }
+        match erc20_address {
+            Some(address) => contracts.insert(0, ("ERC20", address, &*self.erc20)),
+            None => vlog::warn!("There are no ERC20 tokens, their functions aren't self-tested"),
+        }
+        for (contract, to, functions) in contracts {
+            // Overloaded functions share the name, so they are also ordered by the selector.
+            let mut functions: Vec<_> = functions.iter().collect();
+            functions.sort_by(|(a_selector, a), (b_selector, b)| {
+                (&a.name, a_selector).cmp(&(&b.name, b_selector))
+            });
+            for (_, function) in functions {
+                let arguments: Option<Vec<AbiToken>> = function
+                    .inputs
+                    .iter()
+                    .map(|param| Self::fixture_argument(&param.kind, &param.name, &fixtures))
+                    .collect();
+                // E.g. `tokenInfo` can't be answered without ERC20 tokens.
+                let arguments = match arguments {
+                    Some(arguments) => arguments,
+                    None => continue,
+                };
+                let data = function
+                    .encode_input(&arguments)
+                    .map_err(|err| Self::self_test_error(contract, function, &err.to_string()))?;
+                let outcome = self
+                    .execute_at_verified_block(storage, to, data, None, Some(verified_block), None)
+                    .await;
+                let expects_revert = Self::REVERTING_FUNCTIONS.contains(&function.name.as_str());
+                let output = match outcome {
+                    Err(err) if expects_revert && matches!(err.code, ErrorCode::ServerError(3)) => {
+                        continue;
+                    }
+                    _ if expects_revert => {
+                        let reason = "the state-changing call doesn't revert";
+                        return Err(Self::self_test_error(contract, function, reason));
+                    }
+                    Ok(ExecuteOutcome::Answered(output)) => output,
+                    Ok(outcome) => {
+                        let reason = format!("the call isn't answered: {}", outcome.label());
+                        return Err(Self::self_test_error(contract, function, &reason));
+                    }
+                    Err(err) => {
+                        return Err(Self::self_test_error(contract, function, &err.message))
+                    }
+                };
+                // The outputs are always encoded canonically, so extra bytes are a mismatch too.
+                let is_decoded = function
+                    .decode_output(&output)
+                    .map_or(false, |tokens| encode(&tokens) == output);
+                if !is_decoded {
+                    let reason = format!("output 0x{} doesn't match the ABI", hex::encode(output));
+                    return Err(Self::self_test_error(contract, function, &reason));
+                }
+            }
         }
+        Ok(())
     }
 
+    /// Address of the NFT owner at the block, `None` if the NFT isn't owned by any account.
+    async fn nft_owner(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        token_id: TokenId,
+        block: BlockNumber,
+    ) -> std::result::Result<Option<H160>, CallError> {
+        let owner_id = storage
+            .get_nft_owner(token_id, Some(block))
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
+        match owner_id {
+            Some(owner_id) => storage
+                .account_address_by_id(owner_id)
+                .await
+                .map_err(|_| CallError::StorageUnavailable),
+            None => Ok(None),
+        }
+    }
+
+    /// Argument of the `self_test` calls, picked by the parameter name of the stock ABIs, so
+    /// that the view functions are answered, e.g. `ownerOf(tokenId)` gets the fixture NFT and
+    /// `tokenOfOwnerByIndex(owner, index)` gets its owner and zero. Arrays are empty, so
+    /// the batch functions are cheap to call. Returns `None` if there is no such fixture.
+    fn fixture_argument(
+        kind: &ParamType,
+        name: &str,
+        fixtures: &SelfTestFixtures,
+    ) -> Option<AbiToken> {
+        let nft = fixtures.nft.as_ref();
+        let argument = match kind {
+            ParamType::Address => match name {
+                "owner" | "account" => AbiToken::Address(fixtures.owner),
+                "tokenAddress" => AbiToken::Address(nft?.address),
+                "token" => AbiToken::Address(fixtures.erc20_address?),
+                _ => AbiToken::Address(H160::zero()),
+            },
+            ParamType::Bytes => AbiToken::Bytes(Vec::new()),
+            ParamType::Int(_) => AbiToken::Int(U256::zero()),
+            ParamType::Uint(_) => match name {
+                "tokenId" | "id" => AbiToken::Uint(U256::from(nft?.id.0)),
+                "limit" => AbiToken::Uint(U256::one()),
+                // Indices, offsets and amounts.
+                _ => AbiToken::Uint(U256::zero()),
+            },
+            ParamType::Bool => AbiToken::Bool(false),
+            ParamType::String => AbiToken::String(String::new()),
+            ParamType::Array(_) => AbiToken::Array(Vec::new()),
+            ParamType::FixedBytes(size) => AbiToken::FixedBytes(vec![0; *size]),
+            ParamType::FixedArray(kind, size) => {
+                AbiToken::FixedArray(vec![Self::fixture_argument(kind, "", fixtures)?; *size])
+            }
+            ParamType::Tuple(kinds) => AbiToken::Tuple(
+                kinds
+                    .iter()
+                    .map(|kind| Self::fixture_argument(kind, "", fixtures))
+                    .collect::<Option<_>>()?,
+            ),
+        };
+        Some(argument)
+    }
+
+    fn self_test_error(contract: &str, function: &Function, reason: &str) -> Error {
+        Error {
+            code: ErrorCode::InternalError,
+            message: format!(
+                "self-test of {}.{} failed: {}",
+                contract, function.name, reason
+            ),
+            data: None,
+        }
+    }
+
+    /// Chain ID of the EIP-712 domain, also reported by `eth_chainId` and `net_version`.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    #[cfg(test)]
+    pub fn has_function(&self, to: H160, selector: Selector) -> bool {
+        if self.is_nft_contract(to) {
+            self.nft_factory.contains_key(&selector) || self.erc1155.contains_key(&selector)
+        } else {
+            self.erc20.contains_key(&selector)
+        }
+    }
+
+    /// Emulates the call of the contract at `to`. The zero address is never an emulated
+    /// contract, so such calls return nothing without accessing the storage.
+    /// `from` is the `eth_call` sender, it's passed to the functions but none of the current
+    /// ones depend on it.
     pub async fn execute(
         &self,
         storage: &mut StorageProcessor<'_>,
         to: H160,
         data: Vec<u8>,
+        block: Option<BlockNumber>,
+        from: Option<H160>,
+    ) -> Result<Vec<u8>> {
+        self.execute_outcome(storage, to, data, block, from)
+            .await
+            .and_then(ExecuteOutcome::into_result)
+    }
+
+    /// Same as `execute` at the last verified block, but takes the address and the calldata
+    /// as `0x`-prefixed hex strings as they come in JSON-RPC, and returns the hex output.
+    pub async fn execute_hex(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        to: &str,
+        data: &str,
+    ) -> Result<String> {
+        let to = Self::decode_hex("to", to)?;
+        if to.len() != H160::len_bytes() {
+            return Err(Error::invalid_params(format!(
+                "to: expected {} bytes, got {}",
+                H160::len_bytes(),
+                to.len()
+            )));
+        }
+        let data = Self::decode_hex("data", data)?;
+        let output = self
+            .execute(storage, H160::from_slice(&to), data, None, None)
+            .await?;
+        Ok(format!("0x{}", hex::encode(output)))
+    }
+
+    fn decode_hex(name: &str, value: &str) -> Result<Vec<u8>> {
+        let digits = value
+            .strip_prefix("0x")
+            .ok_or_else(|| Error::invalid_params(format!("{}: expected 0x-prefixed hex", name)))?;
+        hex::decode(digits).map_err(|err| Error::invalid_params(format!("{}: {}", name, err)))
+    }
+
+    /// Same as `execute`, but tells apart the calls answered with no data from the ones
+    /// to the addresses that aren't emulated contracts.
+    pub async fn execute_outcome(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        to: H160,
+        data: Vec<u8>,
+        block: Option<BlockNumber>,
+        from: Option<H160>,
+    ) -> Result<ExecuteOutcome> {
+        self.execute_at_verified_block(storage, to, data, block, None, from)
+            .await
+    }
+
+    /// Same as `execute_outcome`, but the last verified block isn't looked up again
+    /// if `verified_block` is set. It's only used for the calls without `block`.
+    async fn execute_at_verified_block(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        to: H160,
+        data: Vec<u8>,
+        block: Option<BlockNumber>,
+        verified_block: Option<BlockNumber>,
+        from: Option<H160>,
+    ) -> Result<ExecuteOutcome> {
+        if data.len() > self.max_call_data_size {
+            return Err(Error::invalid_params(format!(
+                "call data exceeds {} bytes",
+                self.max_call_data_size
+            )));
+        }
+        let outcome = if to == self.multicall3_address {
+            self.execute_multicall3(storage, data, block, verified_block)
+                .await
+                .map(ExecuteOutcome::Answered)
+        } else {
+            self.execute_call(storage, to, data, block, verified_block, from)
+                .await
+        }?;
+        metrics::increment_counter!("api.web3.call_outcome", "outcome" => outcome.label());
+        Ok(outcome)
+    }
+
+    /// Answers `decimals` and `symbol` of the genesis tokens without the storage access,
+    /// returns `None` for other calls.
+    fn call_system_token(&self, to: H160, data: &[u8]) -> Option<Vec<u8>> {
+        let token = self.system_tokens.get(&to)?;
+        if !self.is_allowed_token(to) {
+            return None;
+        }
+        let function = Self::function_for_call(&self.erc20, data).ok()?;
+        let result = match function.name.as_str() {
+            "decimals" => encode(&[AbiToken::Uint(U256::from(token.decimals))]),
+            "symbol" => encode(&[AbiToken::String(self.token_symbol(to, &token.symbol))]),
+            _ => return None,
+        };
+        metrics::increment_counter!("api.web3.call_total", "contract" => "erc20", "function" => function.name.clone());
+        Some(result)
+    }
+
+    /// Routes the call to the address without decoding the call data. Multicall3 is dispatched
+    /// before the routing, so it's unknown here.
+    pub(super) async fn classify<S: TokenProvider>(
+        &self,
+        storage: &mut S,
+        to: H160,
+    ) -> Result<Route> {
+        if to.is_zero() {
+            return Ok(Route::Unknown);
+        }
+        if self.is_nft_contract(to) {
+            return Ok(Route::Proxy);
+        }
+        if !self.is_allowed_token(to) {
+            return Ok(Route::Unknown);
+        }
+        self.token_route(storage, to).await
+    }
+
+    /// Checks the address against the allow-list, all tokens are allowed without one.
+    fn is_allowed_token(&self, address: H160) -> bool {
+        self.allowed_tokens
+            .as_ref()
+            .map_or(true, |tokens| tokens.contains(&address))
+    }
+
+    /// Returns whether calls to the address are emulated, the same way as `execute` does.
+    async fn is_emulated_contract(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        address: H160,
+    ) -> Result<bool> {
+        if address.is_zero() {
+            return Ok(false);
+        }
+        if address == self.multicall3_address || self.is_nft_contract(address) {
+            return Ok(true);
+        }
+        if !self.is_allowed_token(address) {
+            return Ok(false);
+        }
+        Ok(self.get_token_metadata(storage, address).await?.is_some())
+    }
+
+    /// Returns `symbol` of the ERC20 token, the override or the stored one with the prefix
+    /// and the suffix. The storage and the logo URIs keep the stored one.
+    fn token_symbol(&self, address: H160, symbol: &str) -> String {
+        match self.symbol_overrides.get(&address) {
+            Some(symbol) => symbol.clone(),
+            None => format!("{}{}{}", self.symbol_prefix, symbol, self.symbol_suffix),
+        }
+    }
+
+    /// Returns `name` of the ERC20 token, see `token_symbol`. It's also the name
+    /// of the EIP-712 domain, since the wallets build the domain from `name`.
+    fn token_name(&self, address: H160, name: &str) -> String {
+        if self.symbol_overrides.contains_key(&address) {
+            name.to_string()
+        } else {
+            format!("{}{}{}", self.symbol_prefix, name, self.symbol_suffix)
+        }
+    }
+
+    /// Returns the logo URI of the ERC20 token built from the configured pattern,
+    /// the empty string without one.
+    fn logo_uri(&self, address: H160, symbol: &str) -> String {
+        self.logo_uri_pattern
+            .as_ref()
+            .map(|pattern| {
+                pattern
+                    .replace("{address}", &format!("{:?}", address))
+                    .replace("{symbol}", symbol)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Classifies the address for `getContractType` the same way `execute` routes the calls.
+    /// Multicall3 isn't a token, so it's reported as unknown.
+    async fn contract_type<S: TokenProvider>(&self, storage: &mut S, address: H160) -> Result<u8> {
+        if self.is_nft_contract(address) {
+            return Ok(Self::CONTRACT_TYPE_NFT);
+        }
+        if address.is_zero() || !self.is_allowed_token(address) {
+            return Ok(Self::CONTRACT_TYPE_UNKNOWN);
+        }
+        let is_erc20 = self.system_tokens.contains_key(&address)
+            || self.get_token_metadata(storage, address).await?.is_some();
+        if is_erc20 {
+            Ok(Self::CONTRACT_TYPE_ERC20)
+        } else {
+            Ok(Self::CONTRACT_TYPE_UNKNOWN)
+        }
+    }
+
+    /// Describes how `execute` would handle the call without executing it.
+    pub async fn explain(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        to: H160,
+        data: &[u8],
+    ) -> Result<CallExplanation> {
+        let (contract, functions) = if to == self.multicall3_address {
+            (Some(ContractKind::Multicall3), Some(&self.multicall3))
+        } else if self.is_nft_contract(to) {
+            let is_erc1155_call = data
+                .get(0..4)
+                .map_or(false, |selector| self.erc1155.contains_key(selector));
+            if is_erc1155_call {
+                (Some(ContractKind::Erc1155), Some(&self.erc1155))
+            } else {
+                (Some(ContractKind::Nft), Some(&self.nft_factory))
+            }
+        } else if (self.system_tokens.contains_key(&to) && self.is_allowed_token(to))
+            || self.is_emulated_contract(storage, to).await?
+        {
+            (Some(ContractKind::Erc20), Some(&self.erc20))
+        } else {
+            (None, None)
+        };
+
+        let function =
+            functions.and_then(|functions| Self::function_for_call(functions, data).ok());
+        let explanation = match function {
+            Some(function) => CallExplanation {
+                contract,
+                function: function.name.clone(),
+                params: Self::decode_params(function, data)
+                    .map(|params| params.iter().map(Self::display_param).collect())
+                    .unwrap_or_default(),
+            },
+            None => CallExplanation {
+                contract,
+                function: "unknown selector".to_string(),
+                params: Vec::new(),
+            },
+        };
+        Ok(explanation)
+    }
+
+    /// Numbers are shown as decimals and addresses with the `0x` prefix,
+    /// unlike the hex strings of the `ethabi` formatting.
+    fn display_param(param: &AbiToken) -> String {
+        match param {
+            AbiToken::Uint(value) => value.to_string(),
+            AbiToken::Address(address) => format!("{:?}", address),
+            AbiToken::Array(values) => format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(Self::display_param)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            _ => param.to_string(),
+        }
+    }
+
+    /// Returns the bytecode reported by `eth_getCode`. There is no real bytecode for the emulated
+    /// contracts, but clients check that it isn't empty before calling a contract, so an EIP-1167
+    /// proxy to the address itself is returned for them. Other addresses have no code.
+    pub async fn get_code(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        address: H160,
+    ) -> Result<Vec<u8>> {
+        if !self.is_emulated_contract(storage, address).await? {
+            return Ok(Vec::new());
+        }
+        let code = Self::EIP1167_PREFIX
+            .iter()
+            .chain(address.as_bytes())
+            .chain(Self::EIP1167_SUFFIX.iter())
+            .copied()
+            .collect();
+        Ok(code)
+    }
+
+    /// Returns the storage slot of the `owner` balance in the canonical ERC20 layout,
+    /// i.e. `keccak256(owner . ERC20_BALANCES_SLOT)`.
+    pub fn erc20_balance_slot(owner: H160) -> H256 {
+        H256(keccak256(&encode(&[
+            AbiToken::Address(owner),
+            AbiToken::Uint(U256::from(Self::ERC20_BALANCES_SLOT)),
+        ])))
+    }
+
+    /// Emulates `eth_getStorageAt` of the ERC20 tokens for the balance slots. Hashes can't be
+    /// reversed, so the slot is compared with the ones of the token holders in the verified state,
+    /// at most `max_scanned_rows` of them. Other slots and addresses have the zero word.
+    pub async fn get_storage_at(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        address: H160,
+        slot: H256,
+        block: Option<BlockNumber>,
+    ) -> Result<H256> {
+        if address.is_zero() || !self.is_allowed_token(address) {
+            return Ok(H256::zero());
+        }
+        let token = match self.get_token_metadata(storage, address).await? {
+            Some(token) => token,
+            None => return Ok(H256::zero()),
+        };
+        let holders = storage
+            .chain()
+            .account_schema()
+            .get_token_holders(token.id, self.max_scanned_rows)
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
+        let owner = match holders
+            .into_iter()
+            .find(|holder| Self::erc20_balance_slot(*holder) == slot)
+        {
+            Some(owner) => owner,
+            None => return Ok(H256::zero()),
+        };
+        let block = self.block_number(storage, block, None).await?;
+        let balance = storage
+            .chain()
+            .account_schema()
+            .get_account_balance_for_block(owner, block, token.id)
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
+        let mut word = [0u8; 32];
+        Self::balance_to_u256(balance)?.to_big_endian(&mut word);
+        Ok(H256(word))
+    }
+
+    /// Executes `aggregate3` of the Multicall3 contract, all the sub-calls are executed
+    /// in one transaction.
+    async fn execute_multicall3(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        data: Vec<u8>,
+        block: Option<BlockNumber>,
+        verified_block: Option<BlockNumber>,
     ) -> Result<Vec<u8>> {
         let mut transaction = storage
             .start_transaction()
             .await
-            .map_err(|_| Error::internal_error())?;
-        let all_functions = if to == self.nft_factory_address {
-            &self.nft_factory
+            .map_err(|_| CallError::StorageUnavailable)?;
+        let output = self
+            .aggregate3(&mut transaction, &data, block, verified_block)
+            .await?;
+        transaction
+            .commit()
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
+        Ok(output)
+    }
+
+    /// Emulates `aggregate3` of the Multicall3 contract, sub-calls are executed as a batch.
+    /// Calls to the Multicall3 itself aren't supported inside of the batch.
+    /// Like on-chain, the sender of the sub-calls is the Multicall3 contract.
+    pub(super) async fn aggregate3<S: NftProvider>(
+        &self,
+        storage: &mut S,
+        data: &[u8],
+        block: Option<BlockNumber>,
+        verified_block: Option<BlockNumber>,
+    ) -> Result<Vec<u8>> {
+        let function = Self::function_for_call(&self.multicall3, data)?;
+        let calls = Self::decode_params(function, data)?
+            .pop()
+            .and_then(AbiToken::into_array)
+            .ok_or_else(|| Self::decode_failed(function, "tuple[]"))?;
+
+        let mut decoded_calls = Vec::with_capacity(calls.len());
+        for call in calls {
+            let call = call
+                .into_tuple()
+                .ok_or_else(|| Self::decode_failed(function, "tuple"))?;
+            let target = Self::address_param(function, &call, 0)?;
+            let allow_failure = call[1]
+                .clone()
+                .into_bool()
+                .ok_or_else(|| Self::decode_failed(function, "bool"))?;
+            let call_data = call[2]
+                .clone()
+                .into_bytes()
+                .ok_or_else(|| Self::decode_failed(function, "bytes"))?;
+            decoded_calls.push((target, allow_failure, call_data));
+        }
+
+        // Collection pages call `ownerOf` for every NFT, so the owners in the latest state
+        // are loaded by a single query. The past owners are loaded one by one.
+        let token_ids: Vec<TokenId> = if block.is_none() {
+            decoded_calls
+                .iter()
+                .filter_map(|(target, _, call_data)| self.owner_of_call(*target, call_data))
+                .collect()
         } else {
-            let token = self
-                .tokens
-                .get_token(&mut transaction, to)
+            Vec::new()
+        };
+        let owners: HashMap<TokenId, Option<H160>> = if token_ids.is_empty() {
+            HashMap::new()
+        } else {
+            let owners = self.owners_of(storage, &token_ids).await?;
+            token_ids.into_iter().zip(owners).collect()
+        };
+
+        let mut owner_results = Vec::with_capacity(decoded_calls.len());
+        let mut sub_calls = Vec::new();
+        for (target, allow_failure, call_data) in decoded_calls {
+            let owner = self
+                .owner_of_call(target, &call_data)
+                .and_then(|token_id| owners.get(&token_id).copied());
+            let owner_result = owner.map(|owner| {
+                metrics::increment_counter!("api.web3.call_total", "contract" => "nft", "function" => "ownerOf");
+                owner
+                    .map(|owner| encode(&[AbiToken::Address(owner)]))
+                    .ok_or_else(|| Self::revert_error("ERC721: owner query for nonexistent token"))
+            });
+            if owner_result.is_none() {
+                sub_calls.push((target, call_data));
+            }
+            owner_results.push((allow_failure, owner_result));
+        }
+        let from = Some(self.multicall3_address);
+        let mut sub_call_results = self
+            .execute_batch(storage, sub_calls, block, verified_block, from)
+            .await
+            .into_iter();
+
+        let mut results = Vec::with_capacity(owner_results.len());
+        for (allow_failure, owner_result) in owner_results {
+            let call_result = match owner_result {
+                Some(owner_result) => owner_result,
+                None => sub_call_results
+                    .next()
+                    .expect("batch returns a result for every call"),
+            };
+            let (success, return_data) = match call_result {
+                Ok(return_data) => (true, return_data),
+                Err(_) if !allow_failure => {
+                    return Err(Self::revert_error("Multicall3: call failed"));
+                }
+                Err(error) => {
+                    // Reverted calls return the revert payload, just like on-chain.
+                    let return_data = match error.data {
+                        Some(Value::String(data)) => {
+                            hex::decode(data.trim_start_matches("0x")).unwrap_or_default()
+                        }
+                        _ => Vec::new(),
+                    };
+                    (false, return_data)
+                }
+            };
+            results.push(AbiToken::Tuple(vec![
+                AbiToken::Bool(success),
+                AbiToken::Bytes(return_data),
+            ]));
+        }
+        Ok(encode(&[AbiToken::Array(results)]))
+    }
+
+    /// Returns the EIP-712 domain separator of the token, version is always "1".
+    /// The name is the one returned by `name`, wallets use it to build the domain.
+    fn domain_separator(&self, name: &str, token_address: H160) -> [u8; 32] {
+        keccak256(&encode(&[
+            AbiToken::FixedBytes(keccak256(Self::EIP712_DOMAIN_TYPE.as_bytes()).to_vec()),
+            AbiToken::FixedBytes(keccak256(name.as_bytes()).to_vec()),
+            AbiToken::FixedBytes(keccak256(b"1").to_vec()),
+            AbiToken::Uint(U256::from(self.chain_id)),
+            AbiToken::Address(token_address),
+        ]))
+    }
+
+    /// Returns the function and the output cache key if the call is one of
+    /// the `IMMUTABLE_NFT_FUNCTIONS` of an NFT contract.
+    fn immutable_nft_call(
+        &self,
+        target: H160,
+        data: &[u8],
+    ) -> Option<(&Function, (TokenId, Selector))> {
+        if !self.is_nft_contract(target) {
+            return None;
+        }
+        let function = Self::function_for_call(&self.nft_factory, data).ok()?;
+        if !Self::IMMUTABLE_NFT_FUNCTIONS.contains(&function.name.as_str()) {
+            return None;
+        }
+        let params = Self::decode_params(function, data).ok()?;
+        let token_id = self.extract_token_id(function, &params).ok()??;
+        Some((function, (token_id, data[0..4].try_into().unwrap())))
+    }
+
+    /// Returns the NFT ID if the call is `ownerOf` of an NFT contract.
+    fn owner_of_call(&self, target: H160, data: &[u8]) -> Option<TokenId> {
+        if !self.is_nft_contract(target) {
+            return None;
+        }
+        let function = Self::function_for_call(&self.nft_factory, data).ok()?;
+        if function.name != "ownerOf" {
+            return None;
+        }
+        let params = Self::decode_params(function, data).ok()?;
+        self.extract_token_id(function, &params).ok()?
+    }
+
+    /// Returns the owners of the NFTs in the latest state in the order of the IDs,
+    /// `None` for the nonexistent ones. All NFTs are loaded by a single storage query.
+    pub async fn owners_of<S: NftProvider>(
+        &self,
+        storage: &mut S,
+        token_ids: &[TokenId],
+    ) -> Result<Vec<Option<H160>>> {
+        let owners: HashMap<TokenId, Option<H160>> = storage
+            .get_nft_owners(token_ids)
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?
+            .into_iter()
+            .collect();
+        // Same as `ownerOf`: NFTs without an owner are owned by the zero address.
+        let owners = token_ids
+            .iter()
+            .map(|token_id| owners.get(token_id).map(|owner| owner.unwrap_or_default()))
+            .collect();
+        Ok(owners)
+    }
+
+    async fn execute_call(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        to: H160,
+        data: Vec<u8>,
+        block: Option<BlockNumber>,
+        verified_block: Option<BlockNumber>,
+        from: Option<H160>,
+    ) -> Result<ExecuteOutcome> {
+        if let Some(outcome) = self.execute_without_storage(to, &data, block).await {
+            return Ok(outcome);
+        }
+        let mut transaction = storage
+            .start_transaction()
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
+        let outcome = self
+            .dispatch_call(&mut transaction, to, &data, block, verified_block, from)
+            .await?;
+        if let ExecuteOutcome::Answered(_) = &outcome {
+            transaction
+                .commit()
                 .await
-                .map_err(|_| Error::internal_error())?;
-            match token {
-                Some(token) if matches!(token.kind, TokenKind::ERC20) => &self.erc20,
-                _ => return Ok(Vec::new()),
+                .map_err(|_| CallError::StorageUnavailable)?;
+        }
+        self.cache_output(to, &data, block, &outcome).await;
+        Ok(outcome)
+    }
+
+    /// Answers the calls that don't need the storage, returns `None` for other calls.
+    async fn execute_without_storage(
+        &self,
+        to: H160,
+        data: &[u8],
+        block: Option<BlockNumber>,
+    ) -> Option<ExecuteOutcome> {
+        if to.is_zero() {
+            return Some(ExecuteOutcome::NotAContract);
+        }
+        if let Some(result) = self.call_system_token(to, data) {
+            return Some(ExecuteOutcome::Answered(result));
+        }
+        let (function, key) = self.output_key(to, data, block)?;
+        let output = self.nft_outputs.get(&key).await?;
+        metrics::increment_counter!("api.web3.call_total", "contract" => "nft", "function" => function.name.clone());
+        Some(ExecuteOutcome::Answered(output))
+    }
+
+    /// Returns the `nft_outputs` key of the call. The NFT may be not minted yet at
+    /// the historical block, so only the latest state is cached.
+    fn output_key(
+        &self,
+        to: H160,
+        data: &[u8],
+        block: Option<BlockNumber>,
+    ) -> Option<(&Function, (TokenId, Selector))> {
+        match block {
+            Some(_) => None,
+            None => self.immutable_nft_call(to, data),
+        }
+    }
+
+    /// Reverts aren't cached, since the nonexistent NFT may be minted later.
+    async fn cache_output(
+        &self,
+        to: H160,
+        data: &[u8],
+        block: Option<BlockNumber>,
+        outcome: &ExecuteOutcome,
+    ) {
+        if let ExecuteOutcome::Answered(result) = outcome {
+            if let Some((_, key)) = self.output_key(to, data, block) {
+                self.nft_outputs.insert(key, result.clone()).await;
+            }
+        }
+    }
+
+    /// Routes the call and executes the function of the emulated contract, the storage is
+    /// only read through the providers.
+    pub(super) async fn dispatch_call<S: NftProvider>(
+        &self,
+        storage: &mut S,
+        to: H160,
+        data: &[u8],
+        block: Option<BlockNumber>,
+        verified_block: Option<BlockNumber>,
+        from: Option<H160>,
+    ) -> Result<ExecuteOutcome> {
+        let (contract, all_functions, token) = match self.classify(storage, to).await? {
+            Route::Proxy => {
+                let is_erc1155_call = data
+                    .get(0..4)
+                    .map_or(false, |selector| self.erc1155.contains_key(selector));
+                if is_erc1155_call {
+                    ("erc1155", &self.erc1155, None)
+                } else {
+                    ("nft", &self.nft_factory, None)
+                }
+            }
+            Route::Erc20(token) => ("erc20", &self.erc20, Some(token)),
+            Route::Unknown if !self.is_allowed_token(to) => {
+                metrics::increment_counter!("api.web3.call_total", "contract" => "erc20", "function" => "not_allowed_token");
+                return Ok(ExecuteOutcome::NotAContract);
+            }
+            Route::NftEmpty | Route::Unknown => {
+                // Nobody holds tokens that don't exist, so wallets get a zero balance
+                // rather than an error, other calls return nothing as usual.
+                let is_balance_call = Self::function_for_call(&self.erc20, data)
+                    .map_or(false, |function| function.name == "balanceOf");
+                if is_balance_call {
+                    metrics::increment_counter!("api.web3.call_total", "contract" => "erc20", "function" => "missing_token_balance");
+                    return Ok(ExecuteOutcome::Answered(encode(&[AbiToken::Uint(
+                        U256::zero(),
+                    )])));
+                }
+                metrics::increment_counter!("api.web3.call_total", "contract" => "erc20", "function" => "missing_token");
+                return Ok(ExecuteOutcome::MissingToken);
             }
         };
-        let selector: Selector = if data.len() >= 4 {
-            data[0..4].try_into().unwrap()
-        } else {
-            return Ok(Vec::new());
+        // Empty data is a call of the fallback function, probes use it to check that the address
+        // accepts plain calls. The proxies accept them and return nothing, like the zkSync
+        // contract does. Tokens have no fallback, but they return nothing too rather than revert.
+        // Addresses that aren't emulated are classified above, so they aren't answered here.
+        if data.is_empty() {
+            metrics::increment_counter!("api.web3.call_total", "contract" => contract, "function" => "fallback");
+            return Ok(ExecuteOutcome::Answered(Vec::new()));
+        }
+        let function = match Self::function_for_call(all_functions, data) {
+            Ok(function) => function,
+            Err(err) => {
+                metrics::increment_counter!("api.web3.call_total", "contract" => contract, "function" => "unknown_selector");
+                // Data too short to contain a selector can't be classified.
+                return match data.get(0..4) {
+                    Some(selector) => Ok(ExecuteOutcome::UnknownSelector(
+                        selector.try_into().unwrap(),
+                    )),
+                    None => Err(err),
+                };
+            }
         };
-        let function = if let Some(function) = all_functions.get(&selector) {
-            function
-        } else {
-            return Ok(Vec::new());
+        let params = match Self::decode_params(function, data) {
+            Ok(params) => params,
+            Err(err) => {
+                metrics::increment_counter!("api.web3.call_total", "contract" => contract, "function" => "invalid_arguments");
+                return Err(err);
+            }
         };
-        let params = if let Ok(params) = function.decode_input(&data[4..]) {
-            params
+
+        let start = Instant::now();
+        let result = if contract == "erc1155" {
+            self.call_erc1155_function(storage, function, params, block, verified_block, from)
+                .await
         } else {
-            return Ok(Vec::new());
+            self.call_function(
+                storage,
+                to,
+                function,
+                params,
+                token,
+                block,
+                verified_block,
+                from,
+            )
+            .await
         };
+        vlog::debug!(
+            contract,
+            function = %function.name,
+            success = result.is_ok(),
+            "Emulated eth_call took {:?}",
+            start.elapsed()
+        );
+        metrics::histogram!("api.web3.call", start.elapsed(), "contract" => contract, "function" => function.name.clone());
+        metrics::increment_counter!("api.web3.call_total", "contract" => contract, "function" => function.name.clone());
+        Ok(ExecuteOutcome::Answered(result?))
+    }
 
-        let result = if to == self.nft_factory_address {
+    /// Executes the function of the emulated contract. The results don't depend on
+    /// the sender yet, `_from` is there for the functions gated by the caller.
+    #[allow(clippy::too_many_arguments)]
+    async fn call_function<S: NftProvider>(
+        &self,
+        transaction: &mut S,
+        to: H160,
+        function: &Function,
+        params: Vec<AbiToken>,
+        token: Option<TokenMetadata>,
+        block: Option<BlockNumber>,
+        verified_block: Option<BlockNumber>,
+        _from: Option<H160>,
+    ) -> Result<Vec<u8>> {
+        let result = if self.is_nft_contract(to) {
             match function.name.as_str() {
+                // All NFTs belong to the same collection.
+                "name" => encode(&[AbiToken::String(self.collection_name.clone())]),
+                "symbol" => encode(&[AbiToken::String(self.collection_symbol.clone())]),
                 "creatorId" => {
-                    let token_id = params[0]
-                        .clone()
-                        .into_uint()
-                        .ok_or_else(Error::internal_error)?;
-                    if let Some(nft) = self.get_nft(&mut transaction, token_id).await? {
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
                         encode(&[AbiToken::Uint(U256::from(nft.creator_id.0))])
                     } else {
-                        return Err(Self::revert_error(
-                            "execution reverted: creator ID query for nonexistent token",
-                        ));
+                        return Err(Self::revert_error("creator ID query for nonexistent token"));
                     }
                 }
                 "creatorAddress" => {
-                    let token_id = params[0]
-                        .clone()
-                        .into_uint()
-                        .ok_or_else(Error::internal_error)?;
-                    if let Some(nft) = self.get_nft(&mut transaction, token_id).await? {
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
                         encode(&[AbiToken::Address(nft.creator_address)])
                     } else {
                         return Err(Self::revert_error(
-                            "execution reverted: creator address query for nonexistent token",
+                            "creator address query for nonexistent token",
                         ));
                     }
                 }
+                "getCreator" => {
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
+                        encode(&[
+                            AbiToken::Uint(U256::from(nft.creator_id.0)),
+                            AbiToken::Address(nft.creator_address),
+                        ])
+                    } else {
+                        return Err(Self::revert_error("creator query for nonexistent token"));
+                    }
+                }
+                "getNftSummary" => {
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
+                        encode(&[
+                            AbiToken::Uint(U256::from(nft.creator_id.0)),
+                            AbiToken::Address(nft.creator_address),
+                            AbiToken::Uint(U256::from(nft.serial_id)),
+                            Self::bytes32_token(nft.content_hash.as_bytes())?,
+                        ])
+                    } else {
+                        return Err(Self::revert_error("summary query for nonexistent token"));
+                    }
+                }
                 "serialId" => {
-                    let token_id = params[0]
-                        .clone()
-                        .into_uint()
-                        .ok_or_else(Error::internal_error)?;
-                    if let Some(nft) = self.get_nft(&mut transaction, token_id).await? {
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
                         encode(&[AbiToken::Uint(U256::from(nft.serial_id))])
+                    } else {
+                        return Err(Self::revert_error("serial ID query for nonexistent token"));
+                    }
+                }
+                "contentHash" => {
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
+                        encode(&[Self::bytes32_token(nft.content_hash.as_bytes())?])
                     } else {
                         return Err(Self::revert_error(
-                            "execution reverted: serial ID query for nonexistent token",
+                            "content hash query for nonexistent token",
                         ));
                     }
                 }
-                "contentHash" => {
-                    let token_id = params[0]
-                        .clone()
-                        .into_uint()
-                        .ok_or_else(Error::internal_error)?;
-                    if let Some(nft) = self.get_nft(&mut transaction, token_id).await? {
-                        encode(&[AbiToken::FixedBytes(nft.content_hash.as_bytes().to_vec())])
+                // Unlike `tokenURI`, never has a gateway prefix or a data URI.
+                "contentHashCid" => {
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
+                        let cid = self.token_cid(nft.content_hash.as_bytes())?;
+                        encode(&[AbiToken::String(format!("ipfs://{}", cid))])
                     } else {
                         return Err(Self::revert_error(
-                            "execution reverted: content hash query for nonexistent token",
+                            "content CID query for nonexistent token",
                         ));
                     }
                 }
                 "tokenURI" => {
-                    let token_id = params[0]
-                        .clone()
-                        .into_uint()
-                        .ok_or_else(Error::internal_error)?;
-                    if let Some(nft) = self.get_nft(&mut transaction, token_id).await? {
-                        let ipfs_cid = Self::ipfs_cid(nft.content_hash.as_bytes());
-                        encode(&[AbiToken::String(format!("ipfs://{}", ipfs_cid))])
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
+                        let token_uri = self.nft_token_uri(&nft)?;
+                        encode(&[AbiToken::String(token_uri)])
+                    } else {
+                        return Err(Self::revert_error(
+                            "ERC721Metadata: URI query for nonexistent token",
+                        ));
+                    }
+                }
+                "getTokenAddress" => {
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
+                        encode(&[AbiToken::Address(nft.address)])
                     } else {
                         return Err(Self::revert_error(
-                            "execution reverted: ERC721Metadata: URI query for nonexistent token",
+                            "token address query for nonexistent token",
                         ));
                     }
                 }
+                "getTokenId" => {
+                    let address = Self::address_param(function, &params, 0)?;
+                    let token = transaction
+                        .get_cached_token(&self.tokens, address)
+                        .await
+                        .map_err(|_| CallError::StorageUnavailable)?
+                        .filter(|token| token.kind == TokenKind::NFT);
+                    if let Some(token) = token {
+                        encode(&[AbiToken::Uint(U256::from(token.id.0))])
+                    } else {
+                        return Err(Self::revert_error("token ID query for nonexistent token"));
+                    }
+                }
                 "balanceOf" => {
-                    let address = params[0]
-                        .clone()
-                        .into_address()
-                        .ok_or_else(Error::internal_error)?;
+                    let address = Self::address_param(function, &params, 0)?;
                     if address.is_zero() {
                         return Err(Self::revert_error(
-                            "execution reverted: ERC721: balance query for the zero address",
+                            "ERC721: balance query for the zero address",
                         ));
                     }
                     let balance = transaction
-                        .chain()
-                        .account_schema()
-                        .get_account_nft_balance(address)
+                        .get_account_nft_balance(address, block)
                         .await
-                        .map_err(|_| Error::internal_error())?;
+                        .map_err(|_| CallError::StorageUnavailable)?;
                     encode(&[AbiToken::Uint(U256::from(balance))])
                 }
-                "ownerOf" => {
-                    let token_id = params[0]
+                // ERC20 balances of the owner, the ones of unknown tokens are zero.
+                "balancesOf" => {
+                    let owner = Self::address_param(function, &params, 0)?;
+                    let tokens = params[1]
                         .clone()
-                        .into_uint()
-                        .ok_or_else(Error::internal_error)?;
-                    if let Some(nft) = self.get_nft(&mut transaction, token_id).await? {
+                        .into_array()
+                        .ok_or_else(|| Self::decode_failed(function, "address[]"))?;
+                    if tokens.len() > Self::MAX_BALANCES_TOKENS {
+                        return Err(Self::revert_error(&format!(
+                            "balancesOf: tokens exceed {}",
+                            Self::MAX_BALANCES_TOKENS
+                        )));
+                    }
+                    let block = self
+                        .block_number(transaction, block, verified_block)
+                        .await?;
+                    let mut balances = Vec::with_capacity(tokens.len());
+                    for token in tokens {
+                        let token = token
+                            .into_address()
+                            .ok_or_else(|| Self::decode_failed(function, "address"))?;
+                        // Tokens that aren't allowed are reported the same as unknown ones.
+                        let metadata = if self.is_allowed_token(token) {
+                            self.get_token_metadata(transaction, token).await?
+                        } else {
+                            None
+                        };
+                        let balance = match metadata {
+                            Some(token) => transaction
+                                .get_account_balance_for_block(owner, block, token.id)
+                                .await
+                                .map_err(|_| CallError::StorageUnavailable)?,
+                            None => BigUint::from(0u32),
+                        };
+                        balances.push(AbiToken::Uint(Self::balance_to_u256(balance)?));
+                    }
+                    encode(&[AbiToken::Array(balances)])
+                }
+                "ownerOf" => {
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
                         let owner_id = transaction
-                            .chain()
-                            .account_schema()
-                            .get_nft_owner(nft.id)
+                            .get_nft_owner(nft.id, block)
                             .await
-                            .map_err(|_| Error::internal_error())?;
+                            .map_err(|_| CallError::StorageUnavailable)?;
                         let owner_address = if let Some(owner_id) = owner_id {
                             let owner_address = transaction
-                                .chain()
-                                .account_schema()
                                 .account_address_by_id(owner_id)
                                 .await
-                                .map_err(|_| Error::internal_error())?;
+                                .map_err(|_| CallError::StorageUnavailable)?;
                             owner_address.unwrap_or_default()
                         } else {
                             H160::zero()
@@ -244,104 +1796,720 @@ impl CallsHelper {
                         encode(&[AbiToken::Address(owner_address)])
                     } else {
                         return Err(Self::revert_error(
-                            "execution reverted: ERC721: owner query for nonexistent token",
+                            "ERC721: owner query for nonexistent token",
                         ));
                     }
                 }
                 "getApproved" => {
-                    let token_id = params[0]
+                    let token_id = self.extract_token_id(function, &params)?;
+                    if let Some(nft) = self.get_nft(transaction, token_id).await? {
+                        let stored = transaction
+                            .get_nft_approved(nft.id)
+                            .await
+                            .map_err(|_| CallError::StorageUnavailable)?;
+                        // Without a stored approval the operators are reported
+                        // by `isApprovedForAll` instead.
+                        let approved = match stored {
+                            Some(operator) => operator,
+                            None if self.get_approved_proxy => self.zksync_proxy_addresses[0],
+                            None => H160::zero(),
+                        };
+                        encode(&[AbiToken::Address(approved)])
+                    } else {
+                        return Err(Self::revert_error(
+                            "ERC721: approved query for nonexistent token",
+                        ));
+                    }
+                }
+                "isApprovedForAll" => {
+                    // All NFT transfers are routed through the zkSync proxy.
+                    let operator = Self::address_param(function, &params, 1)?;
+                    encode(&[AbiToken::Bool(
+                        self.zksync_proxy_addresses.contains(&operator),
+                    )])
+                }
+                "approve" | "setApprovalForAll" => {
+                    // Approvals can't be changed, so the SDKs doing a pre-check should fail fast.
+                    return Err(Self::revert_error(
+                        "approvals are implicit on zkSync; use isApprovedForAll",
+                    ));
+                }
+                "supportsInterface" => {
+                    let interface_id = params[0]
                         .clone()
-                        .into_uint()
-                        .ok_or_else(Error::internal_error)?;
-                    if self.get_nft(&mut transaction, token_id).await?.is_some() {
-                        encode(&[AbiToken::Address(self.zksync_proxy_address)])
+                        .into_fixed_bytes()
+                        .ok_or_else(|| Self::decode_failed(function, "bytes4"))?;
+                    let is_supported = Self::SUPPORTED_INTERFACES
+                        .iter()
+                        .any(|id| id[..] == interface_id[..]);
+                    encode(&[AbiToken::Bool(is_supported)])
+                }
+                // Unlike ERC20 `totalSupply`, it's the number of NFTs, not the sum of balances.
+                "totalSupply" => {
+                    let count = match self.total_supply {
+                        NftTotalSupply::LiveCount => {
+                            let block = self
+                                .block_number(transaction, block, verified_block)
+                                .await?;
+                            let count = transaction
+                                .get_minted_nfts_count(block)
+                                .await
+                                .map_err(|_| CallError::StorageUnavailable)?;
+                            u64::from(count)
+                        }
+                        // IDs from `MIN_NFT_TOKEN_ID` to the last one accepted by `extract_token_id`.
+                        NftTotalSupply::FixedMax => (self.max_nft_token_id as u64 + 1)
+                            .min(NFT_TOKEN_ID_VAL as u64)
+                            .saturating_sub(MIN_NFT_TOKEN_ID as u64),
+                    };
+                    encode(&[AbiToken::Uint(U256::from(count))])
+                }
+                "tokenByIndex" => {
+                    let index = Self::uint_param(function, &params, 0)?;
+                    self.check_scanned_rows(index)?;
+                    let token_id = if index > U256::from(u32::MAX) {
+                        None
+                    } else {
+                        let block = self
+                            .block_number(transaction, block, verified_block)
+                            .await?;
+                        transaction
+                            .get_minted_nft_by_index(block, index.as_u32())
+                            .await
+                            .map_err(|_| CallError::StorageUnavailable)?
+                    };
+                    if let Some(token_id) = token_id {
+                        encode(&[AbiToken::Uint(U256::from(token_id.0))])
+                    } else {
+                        return Err(Self::revert_error(
+                            "ERC721Enumerable: global index out of bounds",
+                        ));
+                    }
+                }
+                "tokenOfOwnerByIndex" => {
+                    let address = Self::address_param(function, &params, 0)?;
+                    let index = Self::uint_param(function, &params, 1)?;
+                    self.check_scanned_rows(index)?;
+                    let token_id = if index > U256::from(u32::MAX) {
+                        None
+                    } else {
+                        transaction
+                            .get_account_nft_by_index(address, index.as_u32(), block)
+                            .await
+                            .map_err(|_| CallError::StorageUnavailable)?
+                    };
+                    if let Some(token_id) = token_id {
+                        encode(&[AbiToken::Uint(U256::from(token_id.0))])
                     } else {
                         return Err(Self::revert_error(
-                            "execution reverted: ERC721: approved query for nonexistent token",
+                            "ERC721Enumerable: owner index out of bounds",
                         ));
                     }
                 }
-                _ => unreachable!(),
+                "getOwnerTokens" => {
+                    let owner = Self::address_param(function, &params, 0)?;
+                    let offset = Self::uint_param(function, &params, 1)?;
+                    let limit = Self::uint_param(function, &params, 2)?;
+                    if limit > U256::from(Self::MAX_OWNER_TOKENS_LIMIT) {
+                        return Err(Self::revert_error(&format!(
+                            "getOwnerTokens: limit exceeds {}",
+                            Self::MAX_OWNER_TOKENS_LIMIT
+                        )));
+                    }
+                    // The offset is below `max_scanned_rows` afterwards, so it fits into `u32`.
+                    self.check_scanned_rows(offset)?;
+                    let token_ids = transaction
+                        .get_account_nfts(owner, offset.as_u32(), limit.as_u32(), block)
+                        .await
+                        .map_err(|_| CallError::StorageUnavailable)?;
+                    let token_ids = token_ids
+                        .into_iter()
+                        .map(|token_id| AbiToken::Uint(U256::from(token_id.0)))
+                        .collect();
+                    encode(&[AbiToken::Array(token_ids)])
+                }
+                "getContractType" => {
+                    let address = Self::address_param(function, &params, 0)?;
+                    let contract_type = self.contract_type(transaction, address).await?;
+                    encode(&[AbiToken::Uint(U256::from(contract_type))])
+                }
+                "tokenInfo" => {
+                    let address = Self::address_param(function, &params, 0)?;
+                    let token = if self.is_allowed_token(address) {
+                        self.get_token_metadata(transaction, address).await?
+                    } else {
+                        None
+                    };
+                    let token = token
+                        .ok_or_else(|| Self::revert_error("tokenInfo query for unknown token"))?;
+                    let logo_uri = self.logo_uri(address, &token.symbol);
+                    encode(&[
+                        AbiToken::String(self.token_name(address, &token.name)),
+                        AbiToken::String(self.token_symbol(address, &token.symbol)),
+                        AbiToken::Uint(U256::from(token.decimals)),
+                        AbiToken::String(logo_uri),
+                    ])
+                }
+                _ => return Err(Self::unimplemented_function_error(function)),
             }
         } else {
-            let token = self
-                .tokens
-                .get_token(&mut transaction, to)
-                .await
-                .map_err(|_| Error::internal_error())?
-                .ok_or_else(Error::internal_error)?;
+            let token = token.ok_or(CallError::TokenNotFound)?;
+            // Legacy tokens (e.g. MKR) return `bytes32` from `name()` and `symbol()`,
+            // but the selector doesn't depend on the return type, so the variant can't be
+            // detected from the call data and the `string` one is always returned.
             match function.name.as_str() {
-                "name" | "symbol" => encode(&[AbiToken::String(token.symbol)]),
+                "name" => encode(&[AbiToken::String(self.token_name(to, &token.name))]),
+                "symbol" => encode(&[AbiToken::String(self.token_symbol(to, &token.symbol))]),
+                // `uint8` and `uint256` variants of `decimals()` share the selector
+                // and both are encoded as a single zero-padded word.
                 "decimals" => encode(&[AbiToken::Uint(U256::from(token.decimals))]),
-                "totalSupply" | "allowance" => encode(&[AbiToken::Uint(U256::max_value())]),
-                "balanceOf" => {
-                    let block = transaction
-                        .chain()
-                        .block_schema()
-                        .get_last_verified_confirmed_block()
+                "totalSupply" => {
+                    let total_supply = transaction
+                        .get_token_total_supply(token.id)
                         .await
-                        .map_err(|_| Error::internal_error())?;
-                    let address = params[0]
-                        .clone()
-                        .into_address()
-                        .ok_or_else(Error::internal_error)?;
+                        .map_err(|_| CallError::StorageUnavailable)?;
+                    encode(&[AbiToken::Uint(Self::balance_to_u256(total_supply)?)])
+                }
+                // There are no allowances in zkSync, so the owner doesn't matter
+                // and the result is defined by the configured policy.
+                "allowance" => {
+                    let spender = Self::address_param(function, &params, 1)?;
+                    let allowance = match self.allowance_policy {
+                        AllowancePolicy::AlwaysMax => U256::max_value(),
+                        AllowancePolicy::AlwaysZero => U256::zero(),
+                        AllowancePolicy::ProxyOnlyMax => {
+                            if self.zksync_proxy_addresses.contains(&spender) {
+                                U256::max_value()
+                            } else {
+                                U256::zero()
+                            }
+                        }
+                    };
+                    encode(&[AbiToken::Uint(allowance)])
+                }
+                "transfer" | "transferFrom" => {
+                    // Otherwise the empty output of a simulated transfer looks like a success.
+                    return Err(Self::revert_error(
+                        "state-changing calls must be submitted as zkSync transactions",
+                    ));
+                }
+                "increaseAllowance" | "decreaseAllowance" => {
+                    // The SDKs treat the empty output as a changed allowance.
+                    return Err(Self::revert_error(
+                        "allowances are implicit on zkSync and can't be changed; use allowance",
+                    ));
+                }
+                // There are no on-chain permits in zkSync, but the SDKs read the nonce
+                // before building one.
+                "nonces" => encode(&[AbiToken::Uint(U256::zero())]),
+                "DOMAIN_SEPARATOR" => encode(&[AbiToken::FixedBytes(
+                    self.domain_separator(&self.token_name(to, &token.name), to)
+                        .to_vec(),
+                )]),
+                "PERMIT_TYPEHASH" => encode(&[AbiToken::FixedBytes(
+                    keccak256(Self::PERMIT_TYPE.as_bytes()).to_vec(),
+                )]),
+                "balanceOf" => {
+                    let block = self
+                        .block_number(transaction, block, verified_block)
+                        .await?;
+                    let address = Self::address_param(function, &params, 0)?;
+                    // A missing balance row is already a zero balance, so this only fails
+                    // if the storage does, and such failures must not look like zero.
                     let balance = transaction
-                        .chain()
-                        .account_schema()
                         .get_account_balance_for_block(address, block, token.id)
                         .await
-                        .map_err(|_| Error::internal_error())?;
-                    encode(&[AbiToken::Uint(u256_from_biguint(balance))])
+                        .map_err(|_| CallError::StorageUnavailable)?;
+                    encode(&[AbiToken::Uint(Self::balance_to_u256(balance)?)])
                 }
-                _ => unreachable!(),
+                _ => return Err(Self::unimplemented_function_error(function)),
             }
         };
-        transaction
-            .commit()
-            .await
-            .map_err(|_| Error::internal_error())?;
         Ok(result)
     }
 
-    async fn get_nft(
+    /// Executes the ERC1155 function, NFTs are reported as tokens with the supply of one.
+    /// The results don't depend on the sender.
+    async fn call_erc1155_function<S: NftProvider>(
         &self,
-        storage: &mut StorageProcessor<'_>,
+        transaction: &mut S,
+        function: &Function,
+        params: Vec<AbiToken>,
+        block: Option<BlockNumber>,
+        verified_block: Option<BlockNumber>,
+        _from: Option<H160>,
+    ) -> Result<Vec<u8>> {
+        let block = self
+            .block_number(transaction, block, verified_block)
+            .await?;
+        let result = match function.name.as_str() {
+            "balanceOf" => {
+                let address = Self::address_param(function, &params, 0)?;
+                let token_id = Self::uint_param(function, &params, 1)?;
+                let balance = self
+                    .erc1155_balance(transaction, address, token_id, block)
+                    .await?;
+                encode(&[AbiToken::Uint(balance)])
+            }
+            "balanceOfBatch" => {
+                let addresses = params[0]
+                    .clone()
+                    .into_array()
+                    .ok_or_else(|| Self::decode_failed(function, "address[]"))?;
+                let token_ids = params[1]
+                    .clone()
+                    .into_array()
+                    .ok_or_else(|| Self::decode_failed(function, "uint256[]"))?;
+                if addresses.len() != token_ids.len() {
+                    return Err(Self::revert_error(
+                        "ERC1155: accounts and ids length mismatch",
+                    ));
+                }
+                let mut balances = Vec::with_capacity(addresses.len());
+                for (address, token_id) in addresses.into_iter().zip(token_ids) {
+                    let address = address
+                        .into_address()
+                        .ok_or_else(|| Self::decode_failed(function, "address"))?;
+                    let token_id = token_id
+                        .into_uint()
+                        .ok_or_else(|| Self::decode_failed(function, "uint256"))?;
+                    let balance = self
+                        .erc1155_balance(transaction, address, token_id, block)
+                        .await?;
+                    balances.push(AbiToken::Uint(balance));
+                }
+                encode(&[AbiToken::Array(balances)])
+            }
+            _ => return Err(Self::unimplemented_function_error(function)),
+        };
+        Ok(result)
+    }
+
+    /// Only NFTs are ERC1155 tokens of the NFT factory, other IDs (e.g. 0, which is ETH)
+    /// have no balance. The allow-list covers the NFT addresses the same as the ERC20 ones.
+    async fn erc1155_balance<S: NftProvider>(
+        &self,
+        transaction: &mut S,
+        address: H160,
         token_id: U256,
-    ) -> Result<Option<NFT>> {
-        if token_id > U256::from(u32::MAX) {
-            return Ok(None);
+        block: BlockNumber,
+    ) -> Result<U256> {
+        if address.is_zero() {
+            return Err(Self::revert_error(
+                "ERC1155: balance query for the zero address",
+            ));
         }
-        let nft = self
+        let token_id =
+            Self::nft_token_id(token_id).filter(|token_id| token_id.0 <= self.max_nft_token_id);
+        let nft = match self.get_nft(transaction, token_id).await? {
+            Some(nft) if self.is_allowed_token(nft.address) => nft,
+            _ => return Ok(U256::zero()),
+        };
+        let balance = transaction
+            .get_account_balance_for_block(address, block, nft.id)
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
+        Self::balance_to_u256(balance).map_err(Error::from)
+    }
+
+    /// Returns the L2 ETH balance of the account at the block, used by `eth_getBalance`.
+    pub async fn native_balance(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        address: H160,
+        block: BlockNumber,
+    ) -> Result<U256> {
+        let balance = storage
+            .chain()
+            .account_schema()
+            .get_account_balance_for_block(address, block, ETH_TOKEN_ID)
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
+        Self::balance_to_u256(balance).map_err(Error::from)
+    }
+
+    /// Checks whether the transfer of `amount` of the token (ERC20 or NFT) from the `from` address
+    /// would succeed, the storage isn't modified. The recipient doesn't affect the outcome,
+    /// since any address can receive tokens in zkSync.
+    pub async fn simulate_transfer(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        token: H160,
+        from: H160,
+        amount: U256,
+    ) -> Result<TransferSimulation> {
+        let token = self
             .tokens
-            .get_nft_by_id(storage, TokenId(token_id.as_u32()))
+            .get_token(storage, token)
             .await
-            .map_err(|_| Error::internal_error())?;
+            .map_err(|_| CallError::StorageUnavailable)?;
+        let token = match token {
+            Some(token) => token,
+            None => return Ok(TransferSimulation::UnknownToken),
+        };
+
+        let has_enough_tokens = if matches!(token.kind, TokenKind::NFT) {
+            let mut account_schema = storage.chain().account_schema();
+            let owner_id = account_schema
+                .get_nft_owner(token.id)
+                .await
+                .map_err(|_| CallError::StorageUnavailable)?;
+            let from_id = account_schema
+                .account_id_by_address(from)
+                .await
+                .map_err(|_| CallError::StorageUnavailable)?;
+            amount == U256::one() && owner_id.is_some() && owner_id == from_id
+        } else {
+            let block = self.block_number(storage, None, None).await?;
+            let balance = storage
+                .chain()
+                .account_schema()
+                .get_account_balance_for_block(from, block, token.id)
+                .await
+                .map_err(|_| CallError::StorageUnavailable)?;
+            Self::balance_to_u256(balance)? >= amount
+        };
+        if has_enough_tokens {
+            Ok(TransferSimulation::Ok)
+        } else {
+            Ok(TransferSimulation::InsufficientBalance)
+        }
+    }
+
+    /// Executes several calls using the same storage access, results are returned in the order
+    /// of the calls and a failed call doesn't affect the other ones. The caller is responsible
+    /// for the transaction, e.g. `aggregate3` runs all of its sub-calls in one.
+    pub(super) async fn execute_batch<S: NftProvider>(
+        &self,
+        storage: &mut S,
+        calls: Vec<(H160, Vec<u8>)>,
+        block: Option<BlockNumber>,
+        verified_block: Option<BlockNumber>,
+        from: Option<H160>,
+    ) -> Vec<Result<Vec<u8>>> {
+        // All calls are at the same block, so it's looked up once rather than by every call.
+        let verified_block = if block.is_some() || calls.is_empty() {
+            verified_block
+        } else {
+            match self.block_number(storage, None, verified_block).await {
+                Ok(block) => Some(block),
+                Err(err) => return calls.iter().map(|_| Err(err.clone())).collect(),
+            }
+        };
+        let mut results = Vec::with_capacity(calls.len());
+        for (to, data) in calls {
+            let outcome = match self.execute_without_storage(to, &data, block).await {
+                Some(outcome) => Ok(outcome),
+                None => {
+                    let outcome = self
+                        .dispatch_call(storage, to, &data, block, verified_block, from)
+                        .await;
+                    if let Ok(outcome) = &outcome {
+                        self.cache_output(to, &data, block, outcome).await;
+                    }
+                    outcome
+                }
+            };
+            results.push(outcome.and_then(ExecuteOutcome::into_result));
+        }
+        results
+    }
+
+    /// Balances fit into `uint256` unless the storage is corrupted, such values are reported
+    /// as an overflow instead of being truncated.
+    fn balance_to_u256(balance: BigUint) -> std::result::Result<U256, CallError> {
+        try_u256_from_biguint(&balance)
+            .ok_or_else(|| CallError::BalanceOverflow(balance.to_string()))
+    }
+
+    /// Returns the metadata of the ERC20 token, the storage is only accessed on the first lookup
+    /// and after the token is invalidated.
+    async fn get_token_metadata<S: TokenProvider>(
+        &self,
+        storage: &mut S,
+        address: H160,
+    ) -> Result<Option<TokenMetadata>> {
+        match self.token_route(storage, address).await? {
+            Route::Erc20(metadata) => Ok(Some(metadata)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Routes the call to the address of the token, see `get_token_metadata`.
+    /// It's never `Route::Proxy`, the proxies aren't tokens.
+    async fn token_route<S: TokenProvider>(&self, storage: &mut S, address: H160) -> Result<Route> {
+        let generation = {
+            let token_metadata = self.token_metadata.read().await;
+            if let Some(metadata) = token_metadata.get(&address) {
+                return Ok(Route::Erc20(metadata.clone()));
+            }
+            self.token_metadata_generation.load(Ordering::Acquire)
+        };
+
+        // `TokenDBCache` is bypassed, it could return the outdated token after an invalidation.
+        let token = storage
+            .get_token(address)
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
+        let token = match token {
+            Some(token) if matches!(token.kind, TokenKind::ERC20) => token,
+            Some(_) => return Ok(Route::NftEmpty),
+            None => return Ok(Route::Unknown),
+        };
+        // Names are optional in the storage, so the symbol is returned for tokens without a name.
+        let name = storage
+            .get_token_name(token.id)
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?
+            .unwrap_or_else(|| token.symbol.clone());
+        let metadata = TokenMetadata {
+            id: token.id,
+            name,
+            symbol: token.symbol,
+            decimals: token.decimals,
+        };
+
+        // The metadata may be outdated if the cache was invalidated while it was loaded.
+        let mut token_metadata = self.token_metadata.write().await;
+        if self.token_metadata_generation.load(Ordering::Acquire) == generation {
+            token_metadata.insert(address, metadata.clone());
+        }
+        Ok(Route::Erc20(metadata))
+    }
+
+    /// Drops the cached metadata of the token, so the next call loads it from the storage.
+    /// Used when the token is re-registered with the corrected metadata.
+    pub async fn invalidate_token(&self, address: H160) {
+        let mut token_metadata = self.token_metadata.write().await;
+        self.token_metadata_generation
+            .fetch_add(1, Ordering::AcqRel);
+        token_metadata.remove(&address);
+    }
+
+    /// Drops the cached metadata of all the tokens, see `invalidate_token`.
+    pub async fn invalidate_all(&self) {
+        let mut token_metadata = self.token_metadata.write().await;
+        self.token_metadata_generation
+            .fetch_add(1, Ordering::AcqRel);
+        token_metadata.clear();
+    }
+
+    /// Preloads the metadata of the ERC20 tokens, so the first calls after the start don't
+    /// wait for the storage. Unknown IDs and NFTs are skipped.
+    pub async fn warm_up(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        token_ids: &[TokenId],
+    ) -> Result<()> {
+        for &token_id in token_ids {
+            let token = self
+                .tokens
+                .get_token(storage, token_id)
+                .await
+                .map_err(|_| CallError::StorageUnavailable)?;
+            if let Some(token) = token {
+                self.get_token_metadata(storage, token.address).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves the block passed to `execute` by `eth_call`. `latest` is the same as no block,
+    /// so the calls get the latest state of NFTs and the batched `ownerOf` as the calls
+    /// without a block do. Other tags are resolved by `resolve_block_tag`.
+    pub async fn resolve_call_block(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        tag: Option<BlockTag>,
+    ) -> Result<Option<BlockNumber>> {
+        match tag {
+            None | Some(BlockTag::Latest) => Ok(None),
+            Some(tag) => self.resolve_block_tag(storage, tag).await.map(Some),
+        }
+    }
+
+    /// Resolves the block of `eth_call` the same way as the other methods: `latest` and
+    /// `finalized` are the last verified block, `committed` is the last committed one,
+    /// `pending` is the last saved one and `earliest` is the genesis block.
+    pub async fn resolve_block_tag(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        tag: BlockTag,
+    ) -> Result<BlockNumber> {
+        resolve_block_number(storage, Some(tag))
+            .await?
+            .ok_or_else(|| Error::invalid_params("Block with such number doesn't exist yet"))
+    }
+
+    /// Checks whether `execute` can get the token metadata without the storage access.
+    #[cfg(test)]
+    pub(super) async fn is_token_metadata_cached(&self, address: H160) -> bool {
+        self.token_metadata.read().await.contains_key(&address)
+    }
+
+    /// Returns the block to execute the call at, the last verified one is used by default.
+    /// It's only looked up in the storage if `verified_block` isn't known yet.
+    async fn block_number<S: TokenProvider>(
+        &self,
+        storage: &mut S,
+        block: Option<BlockNumber>,
+        verified_block: Option<BlockNumber>,
+    ) -> Result<BlockNumber> {
+        if let Some(block) = block.or(verified_block) {
+            return Ok(block);
+        }
+        storage
+            .get_last_verified_confirmed_block()
+            .await
+            .map_err(|_| CallError::StorageUnavailable.into())
+    }
+
+    /// Minted NFTs never change, so `TokenDBCache` keeps them after the first lookup and
+    /// consecutive metadata calls for the same token don't touch the storage.
+    /// Ownership isn't a part of `NFT`, it is always loaded from the storage.
+    /// `None` is an ID rejected by `extract_token_id`, it's reported as a nonexistent NFT.
+    async fn get_nft<S: NftProvider>(
+        &self,
+        storage: &mut S,
+        token_id: Option<TokenId>,
+    ) -> std::result::Result<Option<NFT>, CallError> {
+        let token_id = match token_id {
+            Some(token_id) => token_id,
+            None => return Ok(None),
+        };
+        let nft = storage
+            .get_nft(&self.tokens, token_id)
+            .await
+            .map_err(|_| CallError::StorageUnavailable)?;
         Ok(nft)
     }
 
-    fn bytes_to_base58(source: &[u8]) -> String {
-        let mut digits: [u8; 46] = [0; 46];
-        let mut digit_length: usize = 1;
-        for mut carry in source.iter().map(|a| *a as u32) {
-            for digit in digits.iter_mut().take(digit_length) {
+    /// Wrong-typed arguments are the caller's fault, so they are reported as invalid params.
+    fn decode_failed(function: &Function, expected: &'static str) -> CallError {
+        CallError::DecodeFailed {
+            function: function.name.clone(),
+            expected,
+        }
+    }
+
+    pub(super) fn address_param(
+        function: &Function,
+        params: &[AbiToken],
+        index: usize,
+    ) -> Result<H160> {
+        params
+            .get(index)
+            .cloned()
+            .and_then(AbiToken::into_address)
+            .ok_or_else(|| Self::decode_failed(function, "address").into())
+    }
+
+    pub(super) fn uint_param(
+        function: &Function,
+        params: &[AbiToken],
+        index: usize,
+    ) -> Result<U256> {
+        params
+            .get(index)
+            .cloned()
+            .and_then(AbiToken::into_uint)
+            .ok_or_else(|| Self::decode_failed(function, "uint256").into())
+    }
+
+    /// Decodes the NFT ID passed as the first argument, see `nft_token_id`.
+    /// IDs above the configured ceiling are `None` as well.
+    pub(super) fn extract_token_id(
+        &self,
+        function: &Function,
+        params: &[AbiToken],
+    ) -> Result<Option<TokenId>> {
+        let token_id = Self::uint_param(function, params, 0)?;
+        Ok(Self::nft_token_id(token_id).filter(|token_id| token_id.0 <= self.max_nft_token_id))
+    }
+
+    /// Returns `None` for IDs outside of the NFT range, such IDs are never minted NFTs,
+    /// so the storage doesn't have to be queried for them.
+    pub(super) fn nft_token_id(token_id: U256) -> Option<TokenId> {
+        if token_id < U256::from(MIN_NFT_TOKEN_ID) || token_id >= U256::from(NFT_TOKEN_ID_VAL) {
+            return None;
+        }
+        Some(TokenId(token_id.as_u32()))
+    }
+
+    pub(super) fn bytes_to_base58(source: &[u8]) -> Result<String> {
+        // Each leading zero byte is encoded as a separate zero digit, otherwise it would be lost.
+        let zeros = source.iter().take_while(|byte| **byte == 0).count();
+        // Base58 representation is at most `len * log(256) / log(58)` (~1.38 * len) digits long.
+        let mut digits: Vec<u8> = Vec::with_capacity(source.len() * 138 / 100 + 1);
+        for mut carry in source[zeros..].iter().map(|a| *a as u32) {
+            for digit in digits.iter_mut() {
                 carry += (*digit as u32) * 256;
                 *digit = (carry % 58) as u8;
                 carry /= 58;
             }
 
             while carry > 0 {
-                digits[digit_length] = (carry % 58) as u8;
-                digit_length += 1;
+                digits.push((carry % 58) as u8);
                 carry /= 58;
             }
         }
 
-        let result: Vec<u8> = digits.iter().rev().copied().collect();
+        debug_assert!(digits.iter().all(|digit| *digit < 58));
+        let result: Vec<u8> = std::iter::repeat(0)
+            .take(zeros)
+            .chain(digits.iter().rev().copied())
+            .collect();
         Self::indices_to_alphabet(&result)
     }
 
-    pub fn ipfs_cid(source: &[u8]) -> String {
-        let concat: Vec<u8> = Self::SHA256_MULTI_HASH
+    /// Decodes the output of `bytes_to_base58`, leading `1`s are decoded as zero bytes.
+    /// Only the tests decode the CIDs, the server never does.
+    #[cfg(test)]
+    pub(super) fn from_base58(source: &str) -> std::result::Result<Vec<u8>, Base58Error> {
+        let alphabet = Self::ALPHABET.as_bytes();
+        let zeros = source
+            .bytes()
+            .take_while(|symbol| *symbol == alphabet[0])
+            .count();
+        // Bytes are at most `len * log(58) / log(256)` (~0.74 * len) long, little-endian here.
+        let mut bytes: Vec<u8> = Vec::with_capacity(source.len() * 74 / 100 + 1);
+        for (index, character) in source.char_indices() {
+            let digit = alphabet
+                .iter()
+                .position(|symbol| *symbol as char == character)
+                .ok_or(Base58Error::InvalidCharacter { character, index })?;
+            let mut carry = digit as u32;
+            for byte in bytes.iter_mut() {
+                carry += (*byte as u32) * 58;
+                *byte = (carry % 256) as u8;
+                carry /= 256;
+            }
+
+            while carry > 0 {
+                bytes.push((carry % 256) as u8);
+                carry /= 256;
+            }
+        }
+
+        Ok(std::iter::repeat(0)
+            .take(zeros)
+            .chain(bytes.into_iter().rev())
+            .collect())
+    }
+
+    /// Returns the multihash prefix, i.e. the hash function code and the digest length.
+    pub fn multihash_prefix(multihash: Multihash) -> &'static [u8] {
+        match multihash {
+            Multihash::Sha256 => &Self::SHA256_MULTI_HASH,
+            Multihash::Keccak256 => &Self::KECCAK256_MULTI_HASH,
+            Multihash::Blake2b256 => &Self::BLAKE2B256_MULTI_HASH,
+            Multihash::Blake2s256 => &Self::BLAKE2S256_MULTI_HASH,
+        }
+    }
+
+    /// Returns CIDv0, i.e. the base58 encoded multihash of the given digest.
+    pub fn ipfs_cid(multihash: Multihash, source: &[u8]) -> Result<String> {
+        let concat: Vec<u8> = Self::multihash_prefix(multihash)
             .iter()
             .chain(source.iter())
             .copied()
@@ -349,11 +2517,150 @@ impl CallsHelper {
         Self::bytes_to_base58(&concat)
     }
 
-    fn indices_to_alphabet(indices: &[u8]) -> String {
-        let mut output = String::new();
-        for i in indices {
-            output.push(Self::ALPHABET.as_bytes()[*i as usize] as char)
+    /// Encodes bytes using lowercase RFC 4648 base32 alphabet without padding.
+    fn bytes_to_base32(source: &[u8]) -> String {
+        let alphabet = Self::BASE32_ALPHABET.as_bytes();
+        let mut output = String::with_capacity((source.len() * 8 + 4) / 5);
+        let mut buffer: u32 = 0;
+        let mut bits: u32 = 0;
+        for byte in source {
+            buffer = (buffer << 8) | *byte as u32;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                output.push(alphabet[((buffer >> bits) & 0x1f) as usize] as char);
+            }
+        }
+        if bits > 0 {
+            output.push(alphabet[((buffer << (5 - bits)) & 0x1f) as usize] as char);
         }
         output
     }
+
+    /// Returns CIDv1 in the base32 multibase encoding (`b` prefix) for the given digest.
+    pub fn ipfs_cid_v1(multihash: Multihash, source: &[u8]) -> String {
+        let concat: Vec<u8> = Self::CID_V1_DAG_PB_PREFIX
+            .iter()
+            .chain(Self::multihash_prefix(multihash).iter())
+            .chain(source.iter())
+            .copied()
+            .collect();
+        format!("b{}", Self::bytes_to_base32(&concat))
+    }
+
+    /// Returns CID of the configured version for the digest of the configured hash function.
+    pub fn token_cid(&self, source: &[u8]) -> Result<String> {
+        match self.cid_version {
+            CidVersion::V0 => Self::ipfs_cid(self.content_multihash, source),
+            CidVersion::V1 => Ok(Self::ipfs_cid_v1(self.content_multihash, source)),
+        }
+    }
+
+    /// Returns `tokenURI` for the given content hash: CID with the configured prefix.
+    /// Fails if the digest length differs from the one declared by the multihash prefix,
+    /// since such CID would be rejected by the wallets.
+    pub fn token_uri(&self, source: &[u8]) -> Result<String> {
+        let prefix = Self::multihash_prefix(self.content_multihash);
+        let digest_length = prefix[prefix.len() - 1] as usize;
+        if source.len() != digest_length {
+            return Err(Error {
+                code: ErrorCode::InternalError,
+                message: format!(
+                    "NFT content hash must be {} bytes long, got {}",
+                    digest_length,
+                    source.len()
+                ),
+                data: None,
+            });
+        }
+        Ok(format!(
+            "{}{}",
+            self.token_uri_prefix,
+            self.token_cid(source)?
+        ))
+    }
+
+    /// Returns `tokenURI` of the NFTs with the given content hash without accessing the storage,
+    /// e.g. to precompute the URIs offline. The CID and the prefix are the configured ones,
+    /// so the result is the same as the one of `tokenURI` in the IPFS format.
+    pub fn content_hash_to_token_uri(&self, hash: H256) -> Result<String> {
+        self.token_uri(hash.as_bytes())
+    }
+
+    /// Returns `tokenURI` of the NFT in the configured format.
+    pub fn nft_token_uri(&self, nft: &NFT) -> Result<String> {
+        // The zero hash isn't a content, such collections serve the metadata by the token ID.
+        if let Some(base_token_uri) = &self.base_token_uri {
+            if nft.content_hash.is_zero() {
+                return Ok(format!("{}{}", base_token_uri, nft.id.0));
+            }
+        }
+        let image = self.token_uri(nft.content_hash.as_bytes())?;
+        let token_uri = match self.token_uri_format {
+            TokenUriFormat::Ipfs => image,
+            TokenUriFormat::Data => format!(
+                "data:application/json;base64,{}",
+                base64::encode(Self::build_erc721_metadata(nft, &image))
+            ),
+        };
+        Ok(token_uri)
+    }
+
+    /// Returns the ERC721 metadata JSON of the NFT with the keys in the fixed order:
+    /// `name`, `description`, `image` and `attributes`.
+    pub(super) fn build_erc721_metadata(nft: &NFT, image: &str) -> String {
+        let metadata = Erc721Metadata {
+            name: format!("{} #{}", nft.symbol, nft.id),
+            description: format!(
+                "zkSync NFT #{} created by {:#x}",
+                nft.serial_id, nft.creator_address
+            ),
+            image: image.to_string(),
+            // Provenance for the collections without off-chain metadata.
+            attributes: vec![
+                Erc721Attribute {
+                    trait_type: "creator_id",
+                    value: nft.creator_id.0.into(),
+                },
+                Erc721Attribute {
+                    trait_type: "serial_id",
+                    value: nft.serial_id.into(),
+                },
+                Erc721Attribute {
+                    trait_type: "creator_address",
+                    value: format!("{:#x}", nft.creator_address).into(),
+                },
+            ],
+        };
+        serde_json::to_string(&metadata).expect("metadata is always serializable")
+    }
+
+    /// Encodes the value as `bytes32`, shorter values are right-padded with zeros the same way
+    /// as Solidity converts `bytesN` to `bytes32`. `FixedBytes` of another length would be
+    /// encoded to a word of a wrong width and clients mis-decode it, so longer values are
+    /// reported as an internal error.
+    pub(super) fn bytes32_token(bytes: &[u8]) -> Result<AbiToken> {
+        if bytes.len() > 32 {
+            vlog::error!("Value of {} bytes doesn't fit into bytes32", bytes.len());
+            return Err(Error::internal_error());
+        }
+        let mut word = bytes.to_vec();
+        word.resize(32, 0);
+        Ok(AbiToken::FixedBytes(word))
+    }
+
+    /// Maps base58 digits to the alphabet. Digits out of the alphabet can only come from a bug
+    /// in the encoder, so they are reported as an internal error instead of a panic.
+    pub(super) fn indices_to_alphabet(indices: &[u8]) -> Result<String> {
+        let alphabet = Self::ALPHABET.as_bytes();
+        let mut output = String::with_capacity(indices.len());
+        for i in indices {
+            let symbol = alphabet.get(*i as usize).ok_or_else(|| {
+                vlog::error!("Base58 digit {} is out of the alphabet", i);
+                Error::internal_error()
+            })?;
+            output.push(*symbol as char)
+        }
+        Ok(output)
+    }
 }
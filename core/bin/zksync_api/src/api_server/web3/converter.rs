@@ -8,8 +8,19 @@ use zksync_storage::StorageProcessor;
 // Local uses
 use super::types::{BlockNumber, Bytes, CommonLogData, Log, Transaction, TxData, H160, H256, U256};
 
+/// Panics if the number doesn't fit into `U256`, which can't happen for the amounts bounded
+/// by the circuit. Use `try_u256_from_biguint` for the values that aren't bounded.
 pub fn u256_from_biguint(number: BigUint) -> U256 {
-    U256::from_dec_str(&number.to_string()).unwrap()
+    try_u256_from_biguint(&number).expect("number doesn't fit into U256")
+}
+
+/// Returns `None` if the number doesn't fit into `U256`.
+pub fn try_u256_from_biguint(number: &BigUint) -> Option<U256> {
+    let bytes = number.to_bytes_be();
+    if bytes.len() > 32 {
+        return None;
+    }
+    Some(U256::from_big_endian(&bytes))
 }
 
 pub async fn resolve_block_number(
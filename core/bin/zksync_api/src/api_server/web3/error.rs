@@ -0,0 +1,53 @@
+// External uses
+use jsonrpc_core::ErrorCode;
+use thiserror::Error;
+
+/// Failures of the emulated contract calls. Each one is reported with its own JSON-RPC
+/// error code, so clients can tell a missing token from an unavailable database.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CallError {
+    #[error("storage is unavailable")]
+    StorageUnavailable,
+    #[error("token not found")]
+    TokenNotFound,
+    #[error("invalid argument of {function}: expected {expected}")]
+    DecodeFailed {
+        function: String,
+        expected: &'static str,
+    },
+    #[error("function {0} is not implemented")]
+    UnsupportedFunction(String),
+    #[error("balance overflow: {0} doesn't fit into uint256")]
+    BalanceOverflow(String),
+}
+
+/// Failures of decoding base58 strings, e.g. CIDv0.
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum Base58Error {
+    #[error("invalid base58 character {character:?} at {index}")]
+    InvalidCharacter { character: char, index: usize },
+}
+
+impl CallError {
+    /// Codes of the EIP-1474 are used where there is a matching one.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::StorageUnavailable => ErrorCode::ServerError(-32002),
+            Self::TokenNotFound => ErrorCode::ServerError(-32001),
+            Self::DecodeFailed { .. } => ErrorCode::InvalidParams,
+            Self::UnsupportedFunction(_) => ErrorCode::ServerError(-32004),
+            Self::BalanceOverflow(_) => ErrorCode::ServerError(-32005),
+        }
+    }
+}
+
+impl From<CallError> for jsonrpc_core::Error {
+    fn from(inner: CallError) -> Self {
+        Self {
+            code: inner.code(),
+            message: inner.to_string(),
+            data: None,
+        }
+    }
+}
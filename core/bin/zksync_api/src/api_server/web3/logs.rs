@@ -1,21 +1,24 @@
 // Built-in uses
 use std::collections::HashMap;
-use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 // External uses
-use ethabi::{encode, Contract, Token as AbiToken};
+use ethabi::{encode, Token as AbiToken};
 use jsonrpc_core::{Error, Result};
 use num::{BigUint, Zero};
+use tiny_keccak::keccak256;
 // Workspace uses
+use zksync_config::configs::api::Web3Config;
 use zksync_storage::StorageProcessor;
 use zksync_token_db_cache::TokenDBCache;
 use zksync_types::{Nonce, Token, TokenId, TokenKind, ZkSyncOp, NFT};
 // Local uses
 use super::{
+    abi_dir_from_env, check_abi_dir,
     converter::{log, u256_from_biguint},
+    load_abi,
     types::{Bytes, CommonLogData, Event, Log, H160, H256, U256},
-    NFT_FACTORY_ADDRESS, ZKSYNC_PROXY_ADDRESS,
+    ERC20_ABI, NFT_FACTORY_ADDRESS, ZKSYNC_PROXY_ABI, ZKSYNC_PROXY_ADDRESS,
 };
 
 #[derive(Debug, Clone)]
@@ -24,19 +27,23 @@ pub struct LogsHelper {
     tokens: TokenDBCache,
     zksync_proxy_address: H160,
     nft_factory_address: H160,
+    /// NFT transfers are reported by `erc721_transfer` rather than `erc_transfer`.
+    indexed_nft_transfers: bool,
+    /// NFT mints are followed by `erc4906_metadata_update`.
+    nft_metadata_updates: bool,
 }
 
 impl LogsHelper {
-    pub fn new(invalidate_token_cache_period: Duration) -> Self {
-        let mut path = PathBuf::new();
-        path.push(std::env::var("ZKSYNC_HOME").unwrap_or_else(|_| "/".to_string()));
-        path.push("etc/web3-abi");
-
-        let proxy_abi = std::fs::File::open(path.join("ZkSyncProxy.json")).unwrap();
-        let proxy_contract = Contract::load(proxy_abi).unwrap();
-
-        let erc20_abi = std::fs::File::open(path.join("ERC20.json")).unwrap();
-        let erc20_contract = Contract::load(erc20_abi).unwrap();
+    pub fn new(
+        config: &Web3Config,
+        invalidate_token_cache_period: Duration,
+    ) -> anyhow::Result<Self> {
+        let abi_dir = abi_dir_from_env();
+        if let Some(dir) = &abi_dir {
+            check_abi_dir(dir, &["ZkSyncProxy.json", "ERC20.json"])?;
+        }
+        let proxy_contract = load_abi(abi_dir.as_deref(), "ZkSyncProxy.json", ZKSYNC_PROXY_ABI)?;
+        let erc20_contract = load_abi(abi_dir.as_deref(), "ERC20.json", ERC20_ABI)?;
 
         let topic_by_event: HashMap<_, _> = vec![
             (
@@ -88,16 +95,23 @@ impl LogsHelper {
                 Event::ERCTransfer,
                 erc20_contract.event("Transfer").unwrap().signature(),
             ),
+            // ERC4906 event isn't a part of any ABI we have, so its topic is built here.
+            (
+                Event::ERCMetadataUpdate,
+                H256::from(keccak256(b"MetadataUpdate(uint256)")),
+            ),
         ]
         .into_iter()
         .collect();
 
-        Self {
+        Ok(Self {
             topic_by_event,
             tokens: TokenDBCache::new(invalidate_token_cache_period),
             zksync_proxy_address: H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap(),
             nft_factory_address: H160::from_str(NFT_FACTORY_ADDRESS).unwrap(),
-        }
+            indexed_nft_transfers: config.nft_indexed_transfer_logs,
+            nft_metadata_updates: config.nft_metadata_update_logs,
+        })
     }
 
     pub fn topic_by_event(&self, event: Event) -> Option<H256> {
@@ -411,18 +425,76 @@ impl LogsHelper {
         // The index is equal to 1 because zksync log has index 0.
         let mut index = 1u8.into();
 
+        let is_mint = matches!(op, ZkSyncOp::MintNFTOp(_));
         let info = self.erc_logs_info(op, storage).await?;
+        // Minted NFTs never change, so their metadata only appears with the mint.
+        let minted_nft = info
+            .iter()
+            .find(|(token, ..)| is_mint && token.kind == TokenKind::NFT)
+            .map(|(token, ..)| token.id);
         logs.extend(info.into_iter().filter_map(|(token, from, to, amount)| {
             if amount.is_zero() {
                 None
+            } else if self.indexed_nft_transfers && token.kind == TokenKind::NFT {
+                Some(self.erc721_transfer(token.id, from, to, common_data, &mut index))
             } else {
                 Some(self.erc_transfer(token, from, to, amount, common_data, &mut index))
             }
         }));
+        if let Some(token_id) = minted_nft.filter(|_| self.nft_metadata_updates) {
+            logs.push(self.erc4906_metadata_update(token_id, common_data, &mut index));
+        }
 
         Ok(logs)
     }
 
+    /// Builds `Transfer(address indexed from, address indexed to, uint256 indexed tokenId)`
+    /// log of the NFT factory, all the values are in topics, so the data is empty.
+    /// Unlike `erc_transfer`, it's the layout of the canonical ERC721 event.
+    pub fn erc721_transfer(
+        &self,
+        token_id: TokenId,
+        from: H160,
+        to: H160,
+        common_data: CommonLogData,
+        transaction_log_index: &mut U256,
+    ) -> Log {
+        let mut log = log(
+            self.nft_factory_address,
+            self.topic_by_event(Event::ERCTransfer).unwrap(),
+            Bytes::default(),
+            common_data,
+            *transaction_log_index,
+        );
+        log.topics.extend([
+            H256::from(from),
+            H256::from(to),
+            H256::from_low_u64_be(token_id.0 as u64),
+        ]);
+        *transaction_log_index += 1u8.into();
+        log
+    }
+
+    /// Builds ERC4906 `MetadataUpdate(uint256 _tokenId)` log of the NFT factory, the token ID
+    /// isn't indexed, so it's in the data.
+    pub fn erc4906_metadata_update(
+        &self,
+        token_id: TokenId,
+        common_data: CommonLogData,
+        transaction_log_index: &mut U256,
+    ) -> Log {
+        let data = encode(&[AbiToken::Uint(U256::from(token_id.0))]);
+        let log = log(
+            self.nft_factory_address,
+            self.topic_by_event(Event::ERCMetadataUpdate).unwrap(),
+            data.into(),
+            common_data,
+            *transaction_log_index,
+        );
+        *transaction_log_index += 1u8.into();
+        log
+    }
+
     async fn get_token_by_id(
         &self,
         storage: &mut StorageProcessor<'_>,
@@ -1,6 +1,9 @@
 // Built-in uses
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 // External uses
-
+use anyhow::format_err;
+use ethabi::Contract;
 use jsonrpc_core::{Error, IoHandler, MetaIoHandler, Metadata, Middleware, Result};
 use jsonrpc_http_server::ServerBuilder;
 // Workspace uses
@@ -8,23 +11,87 @@ use jsonrpc_http_server::ServerBuilder;
 use zksync_storage::{ConnectionPool, StorageProcessor};
 use zksync_utils::panic_notify::{spawn_panic_handler, ThreadPanicNotify};
 // Local uses
-use self::{calls::CallsHelper, logs::LogsHelper, rpc_trait::Web3Rpc};
+use self::{logs::LogsHelper, rpc_trait::Web3Rpc};
 
 use tokio::task::JoinHandle;
 use zksync_config::configs::api::{TokenConfig, Web3Config};
-use zksync_types::ChainId;
+use zksync_types::TokenId;
 
 mod calls;
 mod converter;
+mod error;
 mod logs;
+mod provider;
 mod rpc_impl;
 mod rpc_trait;
 #[cfg(test)]
 mod tests;
 mod types;
 
+// The emulation of the contracts is also used outside of the web3 server, e.g. to preflight
+// transfers, to list the emulated functions or to explain the calls, so the helper is a part
+// of the crate API, see `CallsHelper::shared`.
+pub use self::calls::{CallExplanation, CallsHelper, ContractKind, TransferSimulation};
+
 pub const ZKSYNC_PROXY_ADDRESS: &str = "1000000000000000000000000000000000000000";
 pub const NFT_FACTORY_ADDRESS: &str = "2000000000000000000000000000000000000000";
+/// Canonical address of the Multicall3 contract, the same on every network.
+pub const MULTICALL3_ADDRESS: &str = "cA11bde05977b3631167028862bE2a173976CA11";
+
+/// ABIs of the emulated contracts are embedded into the binary, so it doesn't depend on the source tree.
+const ERC20_ABI: &str = include_str!("../../../../../../etc/web3-abi/ERC20.json");
+const NFT_FACTORY_ABI: &str = include_str!("../../../../../../etc/web3-abi/NFTFactory.json");
+const ZKSYNC_PROXY_ABI: &str = include_str!("../../../../../../etc/web3-abi/ZkSyncProxy.json");
+const MULTICALL3_ABI: &str = include_str!("../../../../../../etc/web3-abi/Multicall3.json");
+const ERC1155_ABI: &str = include_str!("../../../../../../etc/web3-abi/ERC1155.json");
+/// If set, ABIs are loaded from this directory instead of the embedded ones (useful for development).
+const ABI_DIR_ENV_VAR: &str = "ZKSYNC_WEB3_ABI_DIR";
+
+/// Returns the directory specified by `ZKSYNC_WEB3_ABI_DIR`, the embedded ABIs are used without it.
+fn abi_dir_from_env() -> Option<PathBuf> {
+    std::env::var_os(ABI_DIR_ENV_VAR).map(PathBuf::from)
+}
+
+/// Checks that the ABI directory exists and contains all of the files, so a misconfigured
+/// directory is reported as a whole rather than by the first file that fails to open.
+fn check_abi_dir(dir: &Path, file_names: &[&str]) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        dir.is_dir(),
+        "ABI directory {} doesn't exist",
+        dir.display()
+    );
+    let missing: Vec<&str> = file_names
+        .iter()
+        .copied()
+        .filter(|file_name| !dir.join(file_name).is_file())
+        .collect();
+    anyhow::ensure!(
+        missing.is_empty(),
+        "ABI directory {} doesn't contain {}",
+        dir.display(),
+        missing.join(", ")
+    );
+    Ok(())
+}
+
+/// Loads the contract ABI either from the directory or from the embedded copy.
+fn load_abi(
+    abi_dir: Option<&Path>,
+    file_name: &str,
+    embedded_abi: &str,
+) -> anyhow::Result<Contract> {
+    match abi_dir {
+        Some(dir) => {
+            let path = dir.join(file_name);
+            let abi = std::fs::File::open(&path)
+                .map_err(|err| format_err!("Cannot open ABI file {}: {}", path.display(), err))?;
+            Contract::load(abi)
+                .map_err(|err| format_err!("Cannot parse ABI {}: {}", path.display(), err))
+        }
+        None => Contract::load(Cursor::new(embedded_abi))
+            .map_err(|err| format_err!("Cannot parse embedded ABI {}: {}", file_name, err)),
+    }
+}
 
 #[derive(Clone)]
 pub struct Web3RpcApp {
@@ -32,7 +99,6 @@ pub struct Web3RpcApp {
     logs_helper: LogsHelper,
     calls_helper: CallsHelper,
     max_block_range: u32,
-    chain_id: ChainId,
 }
 
 impl Web3RpcApp {
@@ -40,20 +106,45 @@ impl Web3RpcApp {
         connection_pool: ConnectionPool,
         config: &Web3Config,
         token_config: &TokenConfig,
-    ) -> Self {
-        Web3RpcApp {
+    ) -> anyhow::Result<Self> {
+        Ok(Web3RpcApp {
             connection_pool,
-            logs_helper: LogsHelper::new(token_config.invalidate_token_cache_period()),
-            calls_helper: CallsHelper::new(token_config.invalidate_token_cache_period()),
+            logs_helper: LogsHelper::new(config, token_config.invalidate_token_cache_period())?,
+            calls_helper: CallsHelper::new(config, token_config.invalidate_token_cache_period())?,
             max_block_range: config.max_block_range,
-            chain_id: ChainId(config.chain_id),
-        }
+        })
     }
 
     pub fn extend<T: Metadata, S: Middleware<T>>(self, io: &mut MetaIoHandler<T, S>) {
         io.extend_with(self.to_delegate())
     }
 
+    /// Preloads the caches of the emulated contracts for all ERC20 tokens.
+    async fn warm_up(&self) -> anyhow::Result<()> {
+        let mut storage = self.connection_pool.access_storage().await?;
+        let token_ids: Vec<TokenId> = storage
+            .tokens_schema()
+            .load_tokens_asc(TokenId(0), None)
+            .await?
+            .into_iter()
+            .map(|token| token.id)
+            .collect();
+        self.calls_helper
+            .warm_up(&mut storage, &token_ids)
+            .await
+            .map_err(|err| format_err!("Cannot warm up the token cache: {}", err.message))
+    }
+
+    /// Checks that the ABIs and the emulation of the contracts are coherent,
+    /// see `CallsHelper::self_test`.
+    async fn self_test(&self) -> anyhow::Result<()> {
+        let mut storage = self.connection_pool.access_storage().await?;
+        self.calls_helper
+            .self_test(&mut storage)
+            .await
+            .map_err(|err| format_err!("Web3 self-test failed: {}", err.message))
+    }
+
     async fn access_storage(&self) -> Result<StorageProcessor<'_>> {
         self.connection_pool
             .access_storage()
@@ -62,14 +153,22 @@ impl Web3RpcApp {
     }
 }
 
-pub fn start_rpc_server(
+pub async fn start_rpc_server(
     connection_pool: ConnectionPool,
     web3_config: &Web3Config,
     token_config: &TokenConfig,
-) -> JoinHandle<()> {
+) -> anyhow::Result<JoinHandle<()>> {
     let addr = web3_config.bind_addr();
 
-    let rpc_app = Web3RpcApp::new(connection_pool, web3_config, token_config);
+    let rpc_app = Web3RpcApp::new(connection_pool, web3_config, token_config)?;
+    // A cold cache only makes the first calls slower, so the server is started anyway.
+    if let Err(err) = rpc_app.warm_up().await {
+        vlog::warn!("{}", err);
+    }
+    // The mismatches only affect the reported functions, so the server is started anyway.
+    if let Err(err) = rpc_app.self_test().await {
+        vlog::warn!("{}", err);
+    }
     let (handler, panic_sender) = spawn_panic_handler();
 
     std::thread::spawn(move || {
@@ -84,5 +183,5 @@ pub fn start_rpc_server(
             .unwrap();
         server.wait();
     });
-    handler
+    Ok(handler)
 }
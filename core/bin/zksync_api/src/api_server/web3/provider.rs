@@ -0,0 +1,263 @@
+//! Storage reads of the emulated contracts, so the dispatch of `eth_call` doesn't depend
+//! on the database and can be tested against an in-memory storage.
+
+// External uses
+use async_trait::async_trait;
+use num::BigUint;
+// Workspace uses
+use zksync_storage::{QueryResult, StorageProcessor};
+use zksync_token_db_cache::TokenDBCache;
+use zksync_types::{AccountId, BlockNumber, Token, TokenId, NFT};
+// Local uses
+use super::types::H160;
+
+/// Tokens and balances read by the ERC20 functions.
+#[async_trait]
+pub trait TokenProvider: Send {
+    /// Returns the token bypassing `TokenDBCache`, it could be outdated after an invalidation.
+    async fn get_token(&mut self, address: H160) -> QueryResult<Option<Token>>;
+
+    /// Returns the token through `TokenDBCache`.
+    async fn get_cached_token(
+        &mut self,
+        tokens: &TokenDBCache,
+        address: H160,
+    ) -> QueryResult<Option<Token>>;
+
+    async fn get_token_name(&mut self, token_id: TokenId) -> QueryResult<Option<String>>;
+
+    async fn get_token_total_supply(&mut self, token_id: TokenId) -> QueryResult<BigUint>;
+
+    async fn get_account_balance_for_block(
+        &mut self,
+        address: H160,
+        block: BlockNumber,
+        token_id: TokenId,
+    ) -> QueryResult<BigUint>;
+
+    async fn get_last_verified_confirmed_block(&mut self) -> QueryResult<BlockNumber>;
+}
+
+/// NFTs, their owners and approvals read by the NFT factory and ERC1155 functions.
+/// `None` block is the latest state.
+#[async_trait]
+pub trait NftProvider: TokenProvider {
+    /// Returns the NFT through `TokenDBCache`, minted NFTs never change.
+    async fn get_nft(
+        &mut self,
+        tokens: &TokenDBCache,
+        token_id: TokenId,
+    ) -> QueryResult<Option<NFT>>;
+
+    async fn get_nft_owner(
+        &mut self,
+        token_id: TokenId,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<Option<AccountId>>;
+
+    async fn account_address_by_id(&mut self, account_id: AccountId) -> QueryResult<Option<H160>>;
+
+    async fn get_nft_approved(&mut self, token_id: TokenId) -> QueryResult<Option<H160>>;
+
+    /// Returns the owners of the minted NFTs in the latest state, `None` for the ones
+    /// without an owner. All NFTs are loaded by a single query.
+    async fn get_nft_owners(
+        &mut self,
+        token_ids: &[TokenId],
+    ) -> QueryResult<Vec<(TokenId, Option<H160>)>>;
+
+    async fn get_account_nft_balance(
+        &mut self,
+        address: H160,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<u32>;
+
+    async fn get_account_nft_by_index(
+        &mut self,
+        address: H160,
+        index: u32,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<Option<TokenId>>;
+
+    async fn get_account_nfts(
+        &mut self,
+        address: H160,
+        offset: u32,
+        limit: u32,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<Vec<TokenId>>;
+
+    async fn get_minted_nfts_count(&mut self, block: BlockNumber) -> QueryResult<u32>;
+
+    async fn get_minted_nft_by_index(
+        &mut self,
+        block: BlockNumber,
+        index: u32,
+    ) -> QueryResult<Option<TokenId>>;
+}
+
+#[async_trait]
+impl<'a> TokenProvider for StorageProcessor<'a> {
+    async fn get_token(&mut self, address: H160) -> QueryResult<Option<Token>> {
+        self.tokens_schema().get_token(address.into()).await
+    }
+
+    async fn get_cached_token(
+        &mut self,
+        tokens: &TokenDBCache,
+        address: H160,
+    ) -> QueryResult<Option<Token>> {
+        tokens.get_token(self, address).await
+    }
+
+    async fn get_token_name(&mut self, token_id: TokenId) -> QueryResult<Option<String>> {
+        self.tokens_schema().get_token_name(token_id).await
+    }
+
+    async fn get_token_total_supply(&mut self, token_id: TokenId) -> QueryResult<BigUint> {
+        self.chain()
+            .account_schema()
+            .get_token_total_supply(token_id)
+            .await
+    }
+
+    async fn get_account_balance_for_block(
+        &mut self,
+        address: H160,
+        block: BlockNumber,
+        token_id: TokenId,
+    ) -> QueryResult<BigUint> {
+        self.chain()
+            .account_schema()
+            .get_account_balance_for_block(address, block, token_id)
+            .await
+    }
+
+    async fn get_last_verified_confirmed_block(&mut self) -> QueryResult<BlockNumber> {
+        self.chain()
+            .block_schema()
+            .get_last_verified_confirmed_block()
+            .await
+    }
+}
+
+#[async_trait]
+impl<'a> NftProvider for StorageProcessor<'a> {
+    async fn get_nft(
+        &mut self,
+        tokens: &TokenDBCache,
+        token_id: TokenId,
+    ) -> QueryResult<Option<NFT>> {
+        tokens.get_nft_by_id(self, token_id).await
+    }
+
+    async fn get_nft_owner(
+        &mut self,
+        token_id: TokenId,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<Option<AccountId>> {
+        let mut account_schema = self.chain().account_schema();
+        match block {
+            Some(block) => {
+                account_schema
+                    .get_nft_owner_for_block(token_id, block)
+                    .await
+            }
+            None => account_schema.get_nft_owner(token_id).await,
+        }
+    }
+
+    async fn account_address_by_id(&mut self, account_id: AccountId) -> QueryResult<Option<H160>> {
+        self.chain()
+            .account_schema()
+            .account_address_by_id(account_id)
+            .await
+    }
+
+    async fn get_nft_approved(&mut self, token_id: TokenId) -> QueryResult<Option<H160>> {
+        self.tokens_schema().get_nft_approved(token_id).await
+    }
+
+    async fn get_nft_owners(
+        &mut self,
+        token_ids: &[TokenId],
+    ) -> QueryResult<Vec<(TokenId, Option<H160>)>> {
+        self.chain()
+            .account_schema()
+            .get_nft_owners(token_ids)
+            .await
+    }
+
+    async fn get_account_nft_balance(
+        &mut self,
+        address: H160,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<u32> {
+        let mut account_schema = self.chain().account_schema();
+        match block {
+            Some(block) => {
+                account_schema
+                    .get_account_nft_balance_for_block(address, block)
+                    .await
+            }
+            None => account_schema.get_account_nft_balance(address).await,
+        }
+    }
+
+    async fn get_account_nft_by_index(
+        &mut self,
+        address: H160,
+        index: u32,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<Option<TokenId>> {
+        let mut account_schema = self.chain().account_schema();
+        match block {
+            Some(block) => {
+                account_schema
+                    .get_account_nft_by_index_for_block(address, index, block)
+                    .await
+            }
+            None => {
+                account_schema
+                    .get_account_nft_by_index(address, index)
+                    .await
+            }
+        }
+    }
+
+    async fn get_account_nfts(
+        &mut self,
+        address: H160,
+        offset: u32,
+        limit: u32,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<Vec<TokenId>> {
+        let mut account_schema = self.chain().account_schema();
+        match block {
+            Some(block) => {
+                account_schema
+                    .get_account_nfts_for_block(address, offset, limit, block)
+                    .await
+            }
+            None => {
+                account_schema
+                    .get_account_nfts(address, offset, limit)
+                    .await
+            }
+        }
+    }
+
+    async fn get_minted_nfts_count(&mut self, block: BlockNumber) -> QueryResult<u32> {
+        self.tokens_schema().get_minted_nfts_count(block).await
+    }
+
+    async fn get_minted_nft_by_index(
+        &mut self,
+        block: BlockNumber,
+        index: u32,
+    ) -> QueryResult<Option<TokenId>> {
+        self.tokens_schema()
+            .get_minted_nft_by_index(block, index)
+            .await
+    }
+}
@@ -10,10 +10,10 @@ use zksync_storage::{
     StorageProcessor,
 };
 use zksync_types::withdrawals::WithdrawalPendingEvent;
-use zksync_types::{ExecutedOperations, TokenId, ZkSyncOp};
+use zksync_types::{ExecutedOperations, ZkSyncOp};
 // Local uses
 use super::{
-    converter::{resolve_block_number, transaction_from_tx_data, u256_from_biguint},
+    converter::{resolve_block_number, transaction_from_tx_data},
     types::{
         BlockInfo, BlockNumber, Bytes, CallRequest, CommonLogData, Filter, Log, Transaction,
         TransactionReceipt, TxData, H160, H2048, H256, U256, U64,
@@ -50,13 +50,10 @@ impl Web3RpcApp {
         let block_number = resolve_block_number(&mut transaction, block)
             .await?
             .ok_or_else(|| Error::invalid_params("Block with such number doesn't exist yet"))?;
-        let balance = transaction
-            .chain()
-            .account_schema()
-            .get_account_balance_for_block(address, block_number, TokenId(0))
-            .await
-            .map_err(|_| Error::internal_error())?;
-        let result = u256_from_biguint(balance);
+        let result = self
+            .calls_helper
+            .native_balance(&mut transaction, address, block_number)
+            .await?;
         metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "get_balance");
         Ok(result)
     }
@@ -253,23 +250,14 @@ impl Web3RpcApp {
             )));
         }
 
-        let topics = if let Some(mut topics) = filter.topics {
-            // If there is non-null topic at the non-first position then return empty vec,
-            // since all our logs contain exactly one topic.
-            let has_not_first = topics
-                .iter()
-                .enumerate()
-                .any(|(i, topic)| i > 0 && topic.is_some());
-            if has_not_first {
-                return Ok(Vec::new());
-            } else if topics.is_empty() {
-                Vec::new()
-            } else {
-                topics.remove(0).unwrap_or_default().0
-            }
-        } else {
-            Vec::new()
-        };
+        // Topics of the log must match the ones of the filter by the position, an empty or `null`
+        // position matches any topic. Only the indexed NFT transfers have more than one topic.
+        let topics: Vec<Vec<H256>> = filter
+            .topics
+            .unwrap_or_default()
+            .into_iter()
+            .map(|topic| topic.unwrap_or_default().0)
+            .collect();
         let addresses = filter.address.map(|a| a.0).unwrap_or_default();
         let mut result = Vec::new();
 
@@ -282,7 +270,14 @@ impl Web3RpcApp {
         for receipt in receipts {
             let logs = self.logs_from_receipt(&mut transaction, receipt).await?;
             let filtered = logs.into_iter().filter(|log| {
-                if !topics.is_empty() && !topics.contains(&log.topics[0]) {
+                let topics_match = topics.iter().enumerate().all(|(i, topic)| {
+                    topic.is_empty()
+                        || log
+                            .topics
+                            .get(i)
+                            .map_or(false, |log_topic| topic.contains(log_topic))
+                });
+                if !topics_match {
                     return false;
                 }
                 if !addresses.is_empty() && !addresses.contains(&log.address) {
@@ -302,19 +297,67 @@ impl Web3RpcApp {
         Ok(result)
     }
 
-    pub async fn _impl_call(self, req: CallRequest, _block: Option<BlockNumber>) -> Result<Bytes> {
+    pub async fn _impl_call(self, req: CallRequest, block: Option<BlockNumber>) -> Result<Bytes> {
         let start = Instant::now();
         let mut storage = self.access_storage().await?;
 
+        let block_number = self
+            .calls_helper
+            .resolve_call_block(&mut storage, block)
+            .await?;
         let result = self
             .calls_helper
-            .execute(&mut storage, req.to, req.data.unwrap_or_default().0)
+            .execute(
+                &mut storage,
+                req.to,
+                req.data.unwrap_or_default().0,
+                block_number,
+                req.from,
+            )
             .await;
 
         metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "call");
         result.map(Bytes)
     }
 
+    pub async fn _impl_get_code(self, address: H160, _block: Option<BlockNumber>) -> Result<Bytes> {
+        let start = Instant::now();
+        let mut storage = self.access_storage().await?;
+
+        let code = self.calls_helper.get_code(&mut storage, address).await?;
+
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "get_code");
+        Ok(Bytes(code))
+    }
+
+    pub async fn _impl_get_storage_at(
+        self,
+        address: H160,
+        position: U256,
+        block: Option<BlockNumber>,
+    ) -> Result<H256> {
+        let start = Instant::now();
+        let mut storage = self.access_storage().await?;
+
+        let block_number = match block {
+            Some(block) => Some(
+                self.calls_helper
+                    .resolve_block_tag(&mut storage, block)
+                    .await?,
+            ),
+            None => None,
+        };
+        let mut slot = [0u8; 32];
+        position.to_big_endian(&mut slot);
+        let word = self
+            .calls_helper
+            .get_storage_at(&mut storage, address, H256(slot), block_number)
+            .await?;
+
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "get_storage_at");
+        Ok(word)
+    }
+
     pub async fn _impl_check_withdrawal(
         self,
         tx_hash: H256,
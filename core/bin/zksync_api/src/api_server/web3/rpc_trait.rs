@@ -25,6 +25,9 @@ pub trait Web3Rpc {
     #[rpc(name = "net_version", returns = "String")]
     fn net_version(&self) -> Result<String>;
 
+    #[rpc(name = "eth_chainId", returns = "U64")]
+    fn chain_id(&self) -> Result<U64>;
+
     #[rpc(name = "web3_clientVersion", returns = "String")]
     fn web3_client_version(&self) -> Result<String>;
 
@@ -94,7 +97,18 @@ pub trait Web3Rpc {
     fn get_logs(&self, filter: Filter) -> BoxFutureResult<Vec<Log>>;
 
     #[rpc(name = "eth_call", returns = "Bytes")]
-    fn call(&self, req: CallRequest, _block: Option<BlockNumber>) -> BoxFutureResult<Bytes>;
+    fn call(&self, req: CallRequest, block: Option<BlockNumber>) -> BoxFutureResult<Bytes>;
+
+    #[rpc(name = "eth_getCode", returns = "Bytes")]
+    fn get_code(&self, address: H160, block: Option<BlockNumber>) -> BoxFutureResult<Bytes>;
+
+    #[rpc(name = "eth_getStorageAt", returns = "H256")]
+    fn get_storage_at(
+        &self,
+        address: H160,
+        position: U256,
+        block: Option<BlockNumber>,
+    ) -> BoxFutureResult<H256>;
 
     #[rpc(name = "zksync_checkWithdrawal", returns = "Vec<String>")]
     fn check_withdrawal(&self, tx_hash: H256) -> BoxFutureResult<Vec<WithdrawalPendingEvent>>;
@@ -102,7 +116,11 @@ pub trait Web3Rpc {
 
 impl Web3Rpc for Web3RpcApp {
     fn net_version(&self) -> Result<String> {
-        Ok(self.chain_id.to_string())
+        Ok(self.calls_helper.chain_id().to_string())
+    }
+
+    fn chain_id(&self) -> Result<U64> {
+        Ok(U64::from(self.calls_helper.chain_id()))
     }
 
     fn web3_client_version(&self) -> Result<String> {
@@ -188,6 +206,19 @@ impl Web3Rpc for Web3RpcApp {
         spawn! { self._impl_call(req, block) }
     }
 
+    fn get_code(&self, address: H160, block: Option<BlockNumber>) -> BoxFutureResult<Bytes> {
+        spawn! { self._impl_get_code(address, block) }
+    }
+
+    fn get_storage_at(
+        &self,
+        address: H160,
+        position: U256,
+        block: Option<BlockNumber>,
+    ) -> BoxFutureResult<H256> {
+        spawn! { self._impl_get_storage_at(address, position, block) }
+    }
+
     fn check_withdrawal(&self, tx_hash: H256) -> BoxFutureResult<Vec<WithdrawalPendingEvent>> {
         spawn! { self._impl_check_withdrawal(tx_hash) }
     }
@@ -1,36 +1,59 @@
 // Built-in uses
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 // External uses
+use async_trait::async_trait;
 use ethabi::{ParamType, Token};
 use futures::future::{join, join5, Future};
 use jsonrpc_core::{Error, ErrorCode, IoHandler, Params};
 use jsonrpc_core_client::{RawClient, RpcError, RpcResult};
 use num::BigUint;
 use serde_json::{Map, Value};
+use tracing::{
+    field::{Field, Visit},
+    span, subscriber, Level, Metadata, Subscriber,
+};
 // Workspace uses
 
-use zksync_storage::{chain::operations_ext::records::Web3TxReceipt, ConnectionPool};
+use zksync_storage::{chain::operations_ext::records::Web3TxReceipt, ConnectionPool, QueryResult};
 use zksync_test_account::ZkSyncAccount;
+use zksync_token_db_cache::TokenDBCache;
 use zksync_types::{
-    tx::ChangePubKeyType, AccountId, AccountUpdate, BlockNumber, ChangePubKeyOp, CloseOp, Deposit,
-    DepositOp, ForcedExitOp, FullExit, FullExitOp, MintNFTOp, Nonce, SwapOp, TokenId, TransferOp,
-    WithdrawNFTOp, WithdrawOp, ZkSyncOp, NFT,
+    block::PendingBlock, tx::ChangePubKeyType, AccountId, AccountUpdate, BlockNumber,
+    ChangePubKeyOp, CloseOp, Deposit, DepositOp, ForcedExitOp, FullExit, FullExitOp, MintNFTOp,
+    Nonce, SwapOp, Token as ZkSyncToken, TokenId, TokenKind, TransferOp, WithdrawNFTOp, WithdrawOp,
+    ZkSyncOp, NFT,
 };
 // Local uses
 use super::{
-    calls::CallsHelper,
-    converter::{transaction_from_tx_data, u256_from_biguint},
-    types::{BlockInfo, Event, Log, Transaction, TransactionReceipt, H160, H256, U256, U64},
-    Web3RpcApp, NFT_FACTORY_ADDRESS, ZKSYNC_PROXY_ADDRESS,
+    calls::{
+        CallExplanation, CallsHelper, ContractKind, ExecuteOutcome, Route, TransferSimulation,
+    },
+    converter::{transaction_from_tx_data, try_u256_from_biguint, u256_from_biguint},
+    error::{Base58Error, CallError},
+    logs::LogsHelper,
+    provider::{NftProvider, TokenProvider},
+    types::{
+        BlockInfo, BlockNumber as BlockTag, CommonLogData, Event, Log, Transaction,
+        TransactionReceipt, H160, H256, U256, U64,
+    },
+    Web3RpcApp, ABI_DIR_ENV_VAR, ERC1155_ABI, ERC20_ABI, MULTICALL3_ABI, MULTICALL3_ADDRESS,
+    NFT_FACTORY_ABI, NFT_FACTORY_ADDRESS, ZKSYNC_PROXY_ADDRESS,
 };
 use crate::api_server::rest::v02::test_utils::TestServerConfig;
-use zksync_config::configs::api::{TokenConfig, Web3Config};
+use zksync_config::configs::api::{
+    AllowancePolicy, CidVersion, Multihash, NftTotalSupply, TokenConfig, TokenUriFormat, Web3Config,
+};
+use zksync_crypto::params::{MIN_NFT_TOKEN_ID, NFT_TOKEN_ID_VAL};
+use zksync_crypto::rand::{Rng, SeedableRng, XorShiftRng};
 
 async fn local_client() -> anyhow::Result<(RawClient, impl Future<Output = RpcResult<()>>)> {
     let cfg = TestServerConfig::default();
     cfg.fill_database().await?;
 
-    let rpc_app = Web3RpcApp::new(cfg.pool, &cfg.config.api.web3, &cfg.config.api.token_config);
+    let rpc_app = Web3RpcApp::new(cfg.pool, &cfg.config.api.web3, &cfg.config.api.token_config)?;
     let mut io = IoHandler::new();
     rpc_app.extend(&mut io);
 
@@ -104,6 +127,32 @@ async fn static_methods() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Checks that `eth_chainId`, `net_version` and the emulated contracts use the configured chain ID.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn chain_id() -> anyhow::Result<()> {
+    let fut = {
+        let (client, server) = local_client().await?;
+        let chain_id = client.call_method("eth_chainId", Params::None);
+        let net_version = client.call_method("net_version", Params::None);
+        join(join(chain_id, net_version), server)
+    };
+    let ((chain_id, net_version), _) = fut.await;
+    assert_eq!(chain_id.unwrap().as_str().unwrap(), "0xf0");
+    assert_eq!(net_version.unwrap().as_str().unwrap(), "240");
+
+    let config = Web3Config {
+        chain_id: 9,
+        ..TestServerConfig::default().config.api.web3
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    assert_eq!(calls_helper.chain_id(), 9);
+    Ok(())
+}
+
 /// Tests `eth_blockNumber` method
 #[tokio::test(flavor = "multi_thread")]
 #[cfg_attr(
@@ -465,7 +514,7 @@ async fn get_block() -> anyhow::Result<()> {
 async fn create_logs() -> anyhow::Result<()> {
     let cfg = TestServerConfig::default();
     cfg.fill_database().await?;
-    let rpc_app = Web3RpcApp::new(cfg.pool, &cfg.config.api.web3, &cfg.config.api.token_config);
+    let rpc_app = Web3RpcApp::new(cfg.pool, &cfg.config.api.web3, &cfg.config.api.token_config)?;
 
     let from_account_id = AccountId(3);
     let from_account = ZkSyncAccount::rand_with_seed([1, 2, 3, 4]);
@@ -798,6 +847,179 @@ async fn create_logs() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Checks that NFT transfers are logged as the canonical ERC721 `Transfer` event
+/// if `nft_indexed_transfer_logs` is set.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn indexed_nft_transfer_logs() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let config = Web3Config {
+        nft_indexed_transfer_logs: true,
+        ..cfg.config.api.web3.clone()
+    };
+    let rpc_app = Web3RpcApp::new(cfg.pool, &config, &cfg.config.api.token_config)?;
+    let mut storage = rpc_app.connection_pool.access_storage().await?;
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+
+    let from_account_id = AccountId(3);
+    let from_account = ZkSyncAccount::rand_with_seed([1, 2, 3, 4]);
+    from_account.set_account_id(Some(from_account_id));
+    let to_account = ZkSyncAccount::rand_with_seed([5, 6, 7, 8]);
+    let tx = from_account
+        .sign_transfer(
+            nft.id,
+            &nft.symbol,
+            BigUint::from(1u32),
+            BigUint::from(0u32),
+            &to_account.address,
+            None,
+            true,
+            Default::default(),
+        )
+        .0;
+    let (from, to) = (tx.from, tx.to);
+    let transfer_op = TransferOp {
+        tx,
+        from: from_account_id,
+        to: AccountId(1474183),
+    };
+    let receipt = Web3TxReceipt {
+        tx_hash: H256::zero().as_bytes().to_vec(),
+        block_number: 0,
+        operation: serde_json::to_value(ZkSyncOp::from(transfer_op)).unwrap(),
+        block_hash: H256::zero().as_bytes().to_vec(),
+        block_index: Some(0),
+        from_account: H160::zero().as_bytes().to_vec(),
+        to_account: Some(H160::zero().as_bytes().to_vec()),
+        success: true,
+    };
+
+    let logs = rpc_app.logs_from_receipt(&mut storage, receipt).await?;
+    // The zkSync `Transfer` log and the NFT one, the zero fee isn't logged.
+    assert_eq!(logs.len(), 2);
+    let log = &logs[1];
+    assert_eq!(log.address, H160::from_str(NFT_FACTORY_ADDRESS).unwrap());
+    assert_eq!(
+        log.topics,
+        vec![
+            rpc_app
+                .logs_helper
+                .topic_by_event(Event::ERCTransfer)
+                .unwrap(),
+            H256::from(from),
+            H256::from(to),
+            H256::from_low_u64_be(nft.id.0 as u64),
+        ]
+    );
+    assert!(log.data.0.is_empty());
+    assert_eq!(log.transaction_log_index, Some(U256::from(1)));
+
+    Ok(())
+}
+
+/// Checks that NFT mints are followed by the ERC4906 `MetadataUpdate` log
+/// if `nft_metadata_update_logs` is set.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_metadata_update_logs() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let config = Web3Config {
+        nft_metadata_update_logs: true,
+        ..cfg.config.api.web3.clone()
+    };
+    let rpc_app = Web3RpcApp::new(cfg.pool, &config, &cfg.config.api.token_config)?;
+    let mut storage = rpc_app.connection_pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(0).into())
+        .await?
+        .unwrap();
+
+    let from_account_id = AccountId(3);
+    let from_account = ZkSyncAccount::rand_with_seed([1, 2, 3, 4]);
+    from_account.set_account_id(Some(from_account_id));
+    let to_account = ZkSyncAccount::rand_with_seed([5, 6, 7, 8]);
+    let tx = from_account
+        .sign_mint_nft(
+            token.id,
+            &token.symbol,
+            H256::repeat_byte(0xaa),
+            BigUint::from(1u32),
+            &to_account.address,
+            Some(Nonce(10)),
+            true,
+        )
+        .0;
+    let mint_nft_op = MintNFTOp {
+        tx,
+        creator_account_id: from_account_id,
+        recipient_account_id: AccountId(1474183),
+    };
+    let created_nft = NFT::new(
+        TokenId(71234),
+        1,
+        from_account_id,
+        from_account.address,
+        H160::from_str("abcd000000000000000000000000000000000000").unwrap(),
+        None,
+        H256::zero(),
+    );
+    let update = (
+        from_account_id,
+        AccountUpdate::MintNFT {
+            token: created_nft,
+            nonce: Nonce(10),
+        },
+    );
+    storage
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(100), &[update], 0)
+        .await?;
+    let receipt = Web3TxReceipt {
+        tx_hash: H256::zero().as_bytes().to_vec(),
+        block_number: 0,
+        operation: serde_json::to_value(ZkSyncOp::from(mint_nft_op)).unwrap(),
+        block_hash: H256::zero().as_bytes().to_vec(),
+        block_index: Some(0),
+        from_account: H160::zero().as_bytes().to_vec(),
+        to_account: Some(H160::zero().as_bytes().to_vec()),
+        success: true,
+    };
+
+    let logs = rpc_app.logs_from_receipt(&mut storage, receipt).await?;
+    // The zkSync `MintNFT` log, the NFT and the fee transfers and the metadata update.
+    assert_eq!(logs.len(), 4);
+    let log = &logs[3];
+    assert_eq!(log.address, H160::from_str(NFT_FACTORY_ADDRESS).unwrap());
+    assert_eq!(
+        log.topics,
+        vec![rpc_app
+            .logs_helper
+            .topic_by_event(Event::ERCMetadataUpdate)
+            .unwrap()]
+    );
+    assert_eq!(
+        log.data.0,
+        ethabi::encode(&[Token::Uint(U256::from(71234))])
+    );
+    assert_eq!(log.transaction_log_index, Some(U256::from(3)));
+
+    Ok(())
+}
+
 /// Tests `eth_getTransactionReceipt` method
 #[tokio::test(flavor = "multi_thread")]
 #[cfg_attr(
@@ -857,7 +1079,7 @@ async fn get_transaction_receipt() -> anyhow::Result<()> {
             pool.clone(),
             &Web3Config::from_env(),
             &TokenConfig::from_env(),
-        );
+        )?;
         rpc_app.tx_receipt(&mut storage, receipt).await?
     };
     assert_eq!(
@@ -880,7 +1102,7 @@ async fn get_logs() -> anyhow::Result<()> {
         pool.clone(),
         &Web3Config::from_env(),
         &TokenConfig::from_env(),
-    );
+    )?;
 
     // Checks that it returns error if `fromBlock` is greater than `toBlock`.
     let fut = {
@@ -910,8 +1132,9 @@ async fn get_logs() -> anyhow::Result<()> {
                 url: "".to_string(),
                 max_block_range: 3,
                 chain_id: 9,
+                ..Web3Config::from_env()
             };
-            let rpc_app = Web3RpcApp::new(pool.clone(), &config, &TokenConfig::from_env());
+            let rpc_app = Web3RpcApp::new(pool.clone(), &config, &TokenConfig::from_env())?;
             let mut io = IoHandler::new();
             rpc_app.extend(&mut io);
 
@@ -1076,6 +1299,10 @@ async fn erc20_calls() -> anyhow::Result<()> {
             .account_schema()
             .get_account_balance_for_block(address, last_block, token.id)
             .await?;
+        storage
+            .tokens_schema()
+            .store_token_name(token.id, "Test Token")
+            .await?;
         (token, address, u256_from_biguint(balance))
     };
 
@@ -1100,7 +1327,7 @@ async fn erc20_calls() -> anyhow::Result<()> {
         &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
     )
     .unwrap();
-    assert_eq!(outputs[0].clone().into_string().unwrap(), token.symbol);
+    assert_eq!(outputs[0].clone().into_string().unwrap(), "Test Token");
 
     // Test `symbol` function.
     let fut = {
@@ -1172,7 +1399,18 @@ async fn erc20_calls() -> anyhow::Result<()> {
         &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
     )
     .unwrap();
-    assert_eq!(outputs[0].clone().into_uint().unwrap(), U256::max_value());
+    let expected_total_supply = {
+        let mut storage = pool.access_storage().await?;
+        storage
+            .chain()
+            .account_schema()
+            .get_token_total_supply(token.id)
+            .await?
+    };
+    assert_eq!(
+        outputs[0].clone().into_uint().unwrap(),
+        u256_from_biguint(expected_total_supply)
+    );
 
     // Test `balanceOf` function.
     let fut = {
@@ -1200,6 +1438,48 @@ async fn erc20_calls() -> anyhow::Result<()> {
     .unwrap();
     assert_eq!(outputs[0].clone().into_uint().unwrap(), balance);
 
+    // Test `balanceOf` function at the past block.
+    let past_block = BlockNumber(1);
+    let past_balance = {
+        let mut storage = pool.access_storage().await?;
+        let balance = storage
+            .chain()
+            .account_schema()
+            .get_account_balance_for_block(address, past_block, token.id)
+            .await?;
+        u256_from_biguint(balance)
+    };
+    let fut = {
+        let (client, server) = local_client().await?;
+        let mut req = Map::new();
+        req.insert(
+            "to".to_string(),
+            Value::String(format!("{:#?}", token.address)),
+        );
+        let address = ethabi::encode(&[Token::Address(address)]);
+        let mut data = "0x70a08231".to_string();
+        data.push_str(hex::encode(address).as_str());
+        req.insert("data".to_string(), Value::String(data));
+        join(
+            client.call_method(
+                "eth_call",
+                Params::Array(vec![
+                    Value::Object(req),
+                    Value::String(format!("{:#x}", past_block.0)),
+                ]),
+            ),
+            server,
+        )
+    };
+    let resp_data = fut.await.0.unwrap();
+    let resp_data = serde_json::from_value::<String>(resp_data).unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Uint(256)],
+        &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(outputs[0].clone().into_uint().unwrap(), past_balance);
+
     // Test `allowance` function.
     let fut = {
         let (client, server) = local_client().await?;
@@ -1388,7 +1668,8 @@ async fn erc721_calls() -> anyhow::Result<()> {
         &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
     )
     .unwrap();
-    let expected_cid = CallsHelper::ipfs_cid(nft.content_hash.as_bytes());
+    let expected_cid =
+        CallsHelper::ipfs_cid(Multihash::Sha256, nft.content_hash.as_bytes()).unwrap();
     assert_eq!(
         outputs[0].clone().into_string().unwrap(),
         format!("ipfs://{}", expected_cid)
@@ -1417,10 +1698,7 @@ async fn erc721_calls() -> anyhow::Result<()> {
         &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
     )
     .unwrap();
-    assert_eq!(
-        outputs[0].clone().into_address().unwrap(),
-        zksync_proxy_address
-    );
+    assert_eq!(outputs[0].clone().into_address().unwrap(), H160::zero());
 
     // Test `ownerOf` function.
     let fut = {
@@ -1462,6 +1740,59 @@ async fn erc721_calls() -> anyhow::Result<()> {
     };
     assert_eq!(outputs[0].clone().into_address().unwrap(), expected_owner);
 
+    // Test `ownerOf` function at the past block.
+    let past_block = BlockNumber(1);
+    let expected_past_owner = {
+        let mut storage = pool.access_storage().await?;
+        let owner_id = storage
+            .chain()
+            .account_schema()
+            .get_nft_owner_for_block(nft.id, past_block)
+            .await?;
+        if let Some(owner_id) = owner_id {
+            storage
+                .chain()
+                .account_schema()
+                .account_address_by_id(owner_id)
+                .await?
+                .unwrap()
+        } else {
+            H160::zero()
+        }
+    };
+    let fut = {
+        let (client, server) = local_client().await?;
+        let mut req = Map::new();
+        req.insert(
+            "to".to_string(),
+            Value::String(format!("{:#?}", nft_factory_address)),
+        );
+        let mut data = "0x6352211e".to_string();
+        data.push_str(hex::encode(token_id.clone()).as_str());
+        req.insert("data".to_string(), Value::String(data));
+        join(
+            client.call_method(
+                "eth_call",
+                Params::Array(vec![
+                    Value::Object(req),
+                    Value::String(format!("{:#x}", past_block.0)),
+                ]),
+            ),
+            server,
+        )
+    };
+    let resp_data = fut.await.0.unwrap();
+    let resp_data = serde_json::from_value::<String>(resp_data).unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Address],
+        &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        outputs[0].clone().into_address().unwrap(),
+        expected_past_owner
+    );
+
     // Test `balanceOf` function.
     let fut = {
         let (client, server) = local_client().await?;
@@ -1499,19 +1830,6057 @@ async fn erc721_calls() -> anyhow::Result<()> {
         U256::from(expected_balance)
     );
 
-    Ok(())
-}
+    // Test `isApprovedForAll` function.
+    for (operator, expected) in [(zksync_proxy_address, true), (expected_owner, false)] {
+        let fut = {
+            let (client, server) = local_client().await?;
+            let mut req = Map::new();
+            req.insert(
+                "to".to_string(),
+                Value::String(format!("{:#?}", nft_factory_address)),
+            );
+            let mut data = "0xe985e9c5".to_string();
+            let params =
+                ethabi::encode(&[Token::Address(expected_owner), Token::Address(operator)]);
+            data.push_str(hex::encode(params).as_str());
+            req.insert("data".to_string(), Value::String(data));
+            join(
+                client.call_method("eth_call", Params::Array(vec![Value::Object(req)])),
+                server,
+            )
+        };
+        let resp_data = fut.await.0.unwrap();
+        let resp_data = serde_json::from_value::<String>(resp_data).unwrap();
+        let outputs = ethabi::decode(
+            &[ParamType::Bool],
+            &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(outputs[0].clone().into_bool().unwrap(), expected);
+    }
 
-#[test]
-#[cfg_attr(
-    not(feature = "api_test"),
-    ignore = "Use `zk test rust-api` command to perform this test"
-)]
-/// Tests that ipfs cid creation algorithm is the same as in smart contract.
-fn ipfs_cid() {
-    // Test data is the same as in `contracts/test/factory_test.ts`
-    let content_hash =
-        H256::from_str("218145f24cb870cc72ec7f0cc734b86f3e9a744666282f99023f022be77aaea6").unwrap();
-    let ipfs_cid = CallsHelper::ipfs_cid(content_hash.as_bytes());
-    assert_eq!(ipfs_cid, "QmQbSVaG7DUjQ9ktPtMnSXReJ29XHezBghcxJeZDsGG7wB")
+    // Test `supportsInterface` function.
+    for (interface_id, expected) in [
+        ([0x80, 0xac, 0x58, 0xcd], true),
+        ([0x5b, 0x5e, 0x13, 0x9f], true),
+        ([0x01, 0xff, 0xc9, 0xa7], true),
+        ([0xff, 0xff, 0xff, 0xff], false),
+    ] {
+        let fut = {
+            let (client, server) = local_client().await?;
+            let mut req = Map::new();
+            req.insert(
+                "to".to_string(),
+                Value::String(format!("{:#?}", nft_factory_address)),
+            );
+            let mut data = "0x01ffc9a7".to_string();
+            let interface_id = ethabi::encode(&[Token::FixedBytes(interface_id.to_vec())]);
+            data.push_str(hex::encode(interface_id).as_str());
+            req.insert("data".to_string(), Value::String(data));
+            join(
+                client.call_method("eth_call", Params::Array(vec![Value::Object(req)])),
+                server,
+            )
+        };
+        let resp_data = fut.await.0.unwrap();
+        let resp_data = serde_json::from_value::<String>(resp_data).unwrap();
+        let outputs = ethabi::decode(
+            &[ParamType::Bool],
+            &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(outputs[0].clone().into_bool().unwrap(), expected);
+    }
+
+    // Checks that `ownerOf` for nonexistent token reverts with the `Error(string)` payload.
+    let fut = {
+        let (client, server) = local_client().await?;
+        let mut req = Map::new();
+        req.insert(
+            "to".to_string(),
+            Value::String(format!("{:#?}", nft_factory_address)),
+        );
+        let mut data = "0x6352211e".to_string();
+        let token_id = ethabi::encode(&[Token::Uint(U256::max_value())]);
+        data.push_str(hex::encode(token_id).as_str());
+        req.insert("data".to_string(), Value::String(data));
+        join(
+            client.call_method("eth_call", Params::Array(vec![Value::Object(req)])),
+            server,
+        )
+    };
+    let error = fut.await.0.unwrap_err();
+    let reason = "ERC721: owner query for nonexistent token";
+    let mut expected_data = vec![0x08, 0xc3, 0x79, 0xa0];
+    expected_data.extend(ethabi::encode(&[Token::String(reason.to_string())]));
+    if let RpcError::JsonRpcError(error) = error {
+        assert_eq!(error.code, ErrorCode::ServerError(3));
+        assert_eq!(error.message, format!("execution reverted: {}", reason));
+        assert_eq!(
+            error.data,
+            Some(Value::String(format!("0x{}", hex::encode(expected_data))))
+        );
+    } else {
+        panic!("Expected JSON RPC error, got {:?}", error);
+    }
+
+    // Test `totalSupply` function.
+    let expected_total_supply = {
+        let mut storage = pool.access_storage().await?;
+        let block = storage
+            .chain()
+            .block_schema()
+            .get_last_verified_confirmed_block()
+            .await?;
+        storage.tokens_schema().get_minted_nfts_count(block).await?
+    };
+    let fut = {
+        let (client, server) = local_client().await?;
+        let mut req = Map::new();
+        req.insert(
+            "to".to_string(),
+            Value::String(format!("{:#?}", nft_factory_address)),
+        );
+        req.insert("data".to_string(), Value::String("0x18160ddd".to_string()));
+        join(
+            client.call_method("eth_call", Params::Array(vec![Value::Object(req)])),
+            server,
+        )
+    };
+    let resp_data = fut.await.0.unwrap();
+    let resp_data = serde_json::from_value::<String>(resp_data).unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Uint(256)],
+        &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        outputs[0].clone().into_uint().unwrap(),
+        U256::from(expected_total_supply)
+    );
+
+    // Test `tokenByIndex` function.
+    let expected_token = {
+        let mut storage = pool.access_storage().await?;
+        let block = storage
+            .chain()
+            .block_schema()
+            .get_last_verified_confirmed_block()
+            .await?;
+        storage
+            .tokens_schema()
+            .get_minted_nft_by_index(block, 0)
+            .await?
+            .unwrap()
+    };
+    let fut = {
+        let (client, server) = local_client().await?;
+        let mut req = Map::new();
+        req.insert(
+            "to".to_string(),
+            Value::String(format!("{:#?}", nft_factory_address)),
+        );
+        let mut data = "0x4f6ccce7".to_string();
+        let index = ethabi::encode(&[Token::Uint(U256::zero())]);
+        data.push_str(hex::encode(index).as_str());
+        req.insert("data".to_string(), Value::String(data));
+        join(
+            client.call_method("eth_call", Params::Array(vec![Value::Object(req)])),
+            server,
+        )
+    };
+    let resp_data = fut.await.0.unwrap();
+    let resp_data = serde_json::from_value::<String>(resp_data).unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Uint(256)],
+        &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        outputs[0].clone().into_uint().unwrap(),
+        U256::from(expected_token.0)
+    );
+
+    // Checks that `tokenByIndex` reverts for the index out of bounds.
+    let fut = {
+        let (client, server) = local_client().await?;
+        let mut req = Map::new();
+        req.insert(
+            "to".to_string(),
+            Value::String(format!("{:#?}", nft_factory_address)),
+        );
+        let mut data = "0x4f6ccce7".to_string();
+        let index = ethabi::encode(&[Token::Uint(U256::from(expected_total_supply))]);
+        data.push_str(hex::encode(index).as_str());
+        req.insert("data".to_string(), Value::String(data));
+        join(
+            client.call_method("eth_call", Params::Array(vec![Value::Object(req)])),
+            server,
+        )
+    };
+    let error = fut.await.0.unwrap_err();
+    if let RpcError::JsonRpcError(error) = error {
+        assert_eq!(error.code, ErrorCode::ServerError(3));
+        assert_eq!(
+            error.message,
+            "execution reverted: ERC721Enumerable: global index out of bounds"
+        );
+    } else {
+        panic!("Expected JSON RPC error, got {:?}", error);
+    }
+
+    // Test `tokenOfOwnerByIndex` function.
+    let expected_token = {
+        let mut storage = pool.access_storage().await?;
+        storage
+            .chain()
+            .account_schema()
+            .get_account_nft_by_index(expected_owner, 0)
+            .await?
+            .unwrap()
+    };
+    let fut = {
+        let (client, server) = local_client().await?;
+        let mut req = Map::new();
+        req.insert(
+            "to".to_string(),
+            Value::String(format!("{:#?}", nft_factory_address)),
+        );
+        let mut data = "0x2f745c59".to_string();
+        let params = ethabi::encode(&[Token::Address(expected_owner), Token::Uint(U256::zero())]);
+        data.push_str(hex::encode(params).as_str());
+        req.insert("data".to_string(), Value::String(data));
+        join(
+            client.call_method("eth_call", Params::Array(vec![Value::Object(req)])),
+            server,
+        )
+    };
+    let resp_data = fut.await.0.unwrap();
+    let resp_data = serde_json::from_value::<String>(resp_data).unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Uint(256)],
+        &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        outputs[0].clone().into_uint().unwrap(),
+        U256::from(expected_token.0)
+    );
+
+    // Checks that `tokenOfOwnerByIndex` reverts for the index out of bounds.
+    let fut = {
+        let (client, server) = local_client().await?;
+        let mut req = Map::new();
+        req.insert(
+            "to".to_string(),
+            Value::String(format!("{:#?}", nft_factory_address)),
+        );
+        let mut data = "0x2f745c59".to_string();
+        let params = ethabi::encode(&[
+            Token::Address(expected_owner),
+            Token::Uint(U256::from(expected_balance)),
+        ]);
+        data.push_str(hex::encode(params).as_str());
+        req.insert("data".to_string(), Value::String(data));
+        join(
+            client.call_method("eth_call", Params::Array(vec![Value::Object(req)])),
+            server,
+        )
+    };
+    let error = fut.await.0.unwrap_err();
+    if let RpcError::JsonRpcError(error) = error {
+        assert_eq!(error.code, ErrorCode::ServerError(3));
+        assert_eq!(
+            error.message,
+            "execution reverted: ERC721Enumerable: owner index out of bounds"
+        );
+    } else {
+        panic!("Expected JSON RPC error, got {:?}", error);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that ipfs cid creation algorithm is the same as in smart contract.
+fn ipfs_cid() {
+    // Test data is the same as in `contracts/test/factory_test.ts`
+    let content_hash =
+        H256::from_str("218145f24cb870cc72ec7f0cc734b86f3e9a744666282f99023f022be77aaea6").unwrap();
+    let ipfs_cid = CallsHelper::ipfs_cid(Multihash::Sha256, content_hash.as_bytes()).unwrap();
+    assert_eq!(ipfs_cid, "QmQbSVaG7DUjQ9ktPtMnSXReJ29XHezBghcxJeZDsGG7wB");
+
+    // Hashes longer than 32 bytes must not overflow the digits buffer.
+    let ipfs_cid = CallsHelper::ipfs_cid(Multihash::Sha256, &[0xff; 64]).unwrap();
+    assert_eq!(
+        ipfs_cid,
+        "84YqymRkXVEz7rFKn8PYy3NYrEwVa3iNjEYfqBdiKKQRFRfWnPGxh5cabTvKkQNRjdbTERneusi1E7TujbDj31psuQ"
+    );
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that cloning `CallsHelper` doesn't copy the ABI tables.
+fn calls_helper_clone() {
+    let calls_helper = CallsHelper::new(&Web3Config::from_env(), Duration::from_secs(1)).unwrap();
+    let clone = calls_helper.clone();
+    assert!(calls_helper.shares_abi_tables(&clone));
+
+    // Helpers created separately have their own tables, so the check above is meaningful.
+    let other = CallsHelper::new(&Web3Config::from_env(), Duration::from_secs(1)).unwrap();
+    assert!(!calls_helper.shares_abi_tables(&other));
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that the shared helper is created once.
+fn calls_helper_shared() {
+    let first = CallsHelper::shared().unwrap();
+    let second = CallsHelper::shared().unwrap();
+    assert!(std::ptr::eq(first, second));
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that digits out of the base58 alphabet are reported as an error instead of a panic.
+fn base58_alphabet_bounds() {
+    assert_eq!(CallsHelper::indices_to_alphabet(&[0, 57]).unwrap(), "1z");
+    let err = CallsHelper::indices_to_alphabet(&[0, 58]).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InternalError);
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that base58 decoding reverts the encoding, including the leading zero bytes.
+fn base58_round_trip() {
+    let mut rng = XorShiftRng::from_seed([0, 1, 2, 3]);
+    for _ in 0..1000 {
+        let zeros = rng.gen_range(0, 4);
+        let len = rng.gen_range(0, 48);
+        let source: Vec<u8> = std::iter::repeat(0)
+            .take(zeros)
+            .chain((0..len).map(|_| rng.gen::<u8>()))
+            .collect();
+        let encoded = CallsHelper::bytes_to_base58(&source).unwrap();
+        assert_eq!(
+            CallsHelper::from_base58(&encoded).unwrap(),
+            source,
+            "{}",
+            encoded
+        );
+    }
+
+    assert_eq!(CallsHelper::bytes_to_base58(&[]).unwrap(), "");
+    assert_eq!(CallsHelper::bytes_to_base58(&[0, 0, 1]).unwrap(), "112");
+    let cid = CallsHelper::from_base58("QmQbSVaG7DUjQ9ktPtMnSXReJ29XHezBghcxJeZDsGG7wB").unwrap();
+    assert_eq!(
+        hex::encode(cid),
+        "1220218145f24cb870cc72ec7f0cc734b86f3e9a744666282f99023f022be77aaea6"
+    );
+    // `0`, `O`, `I` and `l` aren't in the alphabet.
+    assert_eq!(
+        CallsHelper::from_base58("1O1").unwrap_err(),
+        Base58Error::InvalidCharacter {
+            character: 'O',
+            index: 1
+        }
+    );
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that the digests starting with zero bytes keep them in the base58 encoding.
+fn base58_leading_zeros() {
+    let digest =
+        H256::from_str("0000218145f24cb870cc72ec7f0cc734b86f3e9a744666282f99023f022be77a").unwrap();
+    assert_eq!(
+        CallsHelper::bytes_to_base58(digest.as_bytes()).unwrap(),
+        "117iPC22AnxeLLXy6Q9VAGyBprhkKBpXJHAG4Y7xiKB"
+    );
+    // The multihash prefix precedes the zero bytes, so they don't lead in CIDs.
+    let cid = CallsHelper::ipfs_cid(Multihash::Sha256, digest.as_bytes()).unwrap();
+    assert_eq!(cid, "QmNLeppXBXoAGbEyki1J7d9KzxhJiJPxkWbcJ1xDwSAQPB");
+    assert_eq!(
+        CallsHelper::from_base58(&cid).unwrap()[2..],
+        digest.as_bytes()[..]
+    );
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that CIDv0 and CIDv1 encode the same content hash.
+fn ipfs_cid_versions() {
+    let content_hash =
+        H256::from_str("218145f24cb870cc72ec7f0cc734b86f3e9a744666282f99023f022be77aaea6").unwrap();
+    let mut config = Web3Config::from_env();
+
+    config.nft_cid_version = CidVersion::V0;
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        calls_helper.token_cid(content_hash.as_bytes()).unwrap(),
+        "QmQbSVaG7DUjQ9ktPtMnSXReJ29XHezBghcxJeZDsGG7wB"
+    );
+
+    config.nft_cid_version = CidVersion::V1;
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        calls_helper.token_cid(content_hash.as_bytes()).unwrap(),
+        "bafybeibbqfc7etfyodghf3d7btdtjodph2nhirtgfaxzsar7aiv6o6vouy"
+    );
+    assert_eq!(
+        CallsHelper::ipfs_cid_v1(Multihash::Sha256, content_hash.as_bytes()),
+        "bafybeibbqfc7etfyodghf3d7btdtjodph2nhirtgfaxzsar7aiv6o6vouy"
+    );
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that CIDs declare the hash function used to compute the content hash.
+fn ipfs_cid_multihash() {
+    let content_hash =
+        H256::from_str("218145f24cb870cc72ec7f0cc734b86f3e9a744666282f99023f022be77aaea6").unwrap();
+
+    assert_eq!(
+        CallsHelper::multihash_prefix(Multihash::Sha256),
+        &[0x12, 0x20]
+    );
+    assert_eq!(
+        CallsHelper::multihash_prefix(Multihash::Keccak256),
+        &[0x1b, 0x20]
+    );
+    assert_eq!(
+        CallsHelper::multihash_prefix(Multihash::Blake2b256),
+        &[0xa0, 0xe4, 0x02, 0x20]
+    );
+    assert_eq!(
+        CallsHelper::multihash_prefix(Multihash::Blake2s256),
+        &[0xe0, 0xe4, 0x02, 0x20]
+    );
+
+    // The digest is the same, so CIDs only differ in the multihash prefix.
+    assert_eq!(
+        CallsHelper::ipfs_cid_v1(Multihash::Sha256, content_hash.as_bytes()),
+        "bafybeibbqfc7etfyodghf3d7btdtjodph2nhirtgfaxzsar7aiv6o6vouy"
+    );
+    assert_eq!(
+        CallsHelper::ipfs_cid_v1(Multihash::Keccak256, content_hash.as_bytes()),
+        "bafybwibbqfc7etfyodghf3d7btdtjodph2nhirtgfaxzsar7aiv6o6vouy"
+    );
+    assert_eq!(
+        CallsHelper::ipfs_cid_v1(Multihash::Blake2b256, content_hash.as_bytes()),
+        "bafykbzaceaqycrpsjs4hbtds5r7qzrzuxbxt5gtuiztcql4zai7qek7hpkxkm"
+    );
+
+    // `blake2s-256("zkSync NFT")`
+    let blake2s_hash =
+        H256::from_str("24b82a46bfd77e4d800604e080c0a63f8dcc71524c623c75e5216f1ba536a652").unwrap();
+    let mut config = Web3Config::from_env();
+    config.nft_cid_version = CidVersion::V1;
+    config.nft_content_multihash = Multihash::Blake2s256;
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        calls_helper.token_cid(blake2s_hash.as_bytes()).unwrap(),
+        "bafyobzaceaslqksgx7lx4tmaaycobagauy7y3tdrkjggepdv4uqw6g5fg2tfe"
+    );
+    config.nft_cid_version = CidVersion::V0;
+    assert!(CallsHelper::new(&config, Duration::from_secs(1)).is_err());
+
+    let mut config = Web3Config::from_env();
+    config.nft_cid_version = CidVersion::V1;
+    config.nft_content_multihash = Multihash::Blake2b256;
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        calls_helper.token_cid(content_hash.as_bytes()).unwrap(),
+        "bafykbzaceaqycrpsjs4hbtds5r7qzrzuxbxt5gtuiztcql4zai7qek7hpkxkm"
+    );
+
+    // CIDv0 has no place for the hash function, so only SHA256 hashes are allowed.
+    config.nft_cid_version = CidVersion::V0;
+    assert!(CallsHelper::new(&config, Duration::from_secs(1)).is_err());
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that `tokenURI` uses the configured prefix.
+fn token_uri_prefix() {
+    let content_hash =
+        H256::from_str("218145f24cb870cc72ec7f0cc734b86f3e9a744666282f99023f022be77aaea6").unwrap();
+    let mut config = Web3Config::from_env();
+    config.nft_cid_version = CidVersion::V0;
+
+    config.nft_token_uri_prefix = "ipfs://".to_string();
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        calls_helper.token_uri(content_hash.as_bytes()).unwrap(),
+        "ipfs://QmQbSVaG7DUjQ9ktPtMnSXReJ29XHezBghcxJeZDsGG7wB"
+    );
+
+    config.nft_token_uri_prefix = "https://cloudflare-ipfs.com/ipfs/".to_string();
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        calls_helper.token_uri(content_hash.as_bytes()).unwrap(),
+        "https://cloudflare-ipfs.com/ipfs/QmQbSVaG7DUjQ9ktPtMnSXReJ29XHezBghcxJeZDsGG7wB"
+    );
+
+    config.nft_token_uri_prefix = "https://cloudflare-ipfs.com/ipfs".to_string();
+    assert!(CallsHelper::new(&config, Duration::from_secs(1)).is_err());
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that only IDs from the NFT range are looked up as NFTs.
+fn nft_token_id_range() {
+    for (token_id, expected) in [
+        (U256::from(MIN_NFT_TOKEN_ID - 1), None),
+        (
+            U256::from(MIN_NFT_TOKEN_ID),
+            Some(TokenId(MIN_NFT_TOKEN_ID)),
+        ),
+        (
+            U256::from(NFT_TOKEN_ID_VAL - 1),
+            Some(TokenId(NFT_TOKEN_ID_VAL - 1)),
+        ),
+        (U256::from(NFT_TOKEN_ID_VAL), None),
+        (U256::from(u32::MAX), None),
+        (U256::from(u32::MAX) + 1, None),
+    ] {
+        assert_eq!(
+            CallsHelper::nft_token_id(token_id),
+            expected,
+            "{}",
+            token_id
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that the NFT ID argument is range-checked and malformed arguments are errors.
+fn extract_token_id_bounds() {
+    let calls_helper = CallsHelper::new(&Web3Config::from_env(), Duration::from_secs(1)).unwrap();
+    let contract = ethabi::Contract::load(NFT_FACTORY_ABI.as_bytes()).unwrap();
+    let owner_of = contract.function("ownerOf").unwrap();
+    for (token_id, expected) in [
+        (U256::from(MIN_NFT_TOKEN_ID - 1), None),
+        (
+            U256::from(MIN_NFT_TOKEN_ID),
+            Some(TokenId(MIN_NFT_TOKEN_ID)),
+        ),
+        (
+            U256::from(NFT_TOKEN_ID_VAL - 1),
+            Some(TokenId(NFT_TOKEN_ID_VAL - 1)),
+        ),
+        (U256::from(NFT_TOKEN_ID_VAL), None),
+        (U256::max_value(), None),
+    ] {
+        assert_eq!(
+            calls_helper
+                .extract_token_id(owner_of, &[Token::Uint(token_id)])
+                .unwrap(),
+            expected,
+            "{}",
+            token_id
+        );
+    }
+    assert!(calls_helper.extract_token_id(owner_of, &[]).is_err());
+    assert!(calls_helper
+        .extract_token_id(owner_of, &[Token::Address(H160::zero())])
+        .is_err());
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that wrong-typed arguments are reported as invalid params naming the function.
+fn wrong_typed_params() {
+    let contract = ethabi::Contract::load(ERC20_ABI.as_bytes()).unwrap();
+    let balance_of = contract.function("balanceOf").unwrap();
+
+    let params = [Token::Uint(U256::from(1))];
+    let error = CallsHelper::address_param(balance_of, &params, 0).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidParams);
+    assert_eq!(
+        error.message,
+        "invalid argument of balanceOf: expected address"
+    );
+    assert_eq!(
+        CallsHelper::uint_param(balance_of, &params, 0).unwrap(),
+        U256::from(1)
+    );
+
+    let params = [Token::Address(H160::zero())];
+    let error = CallsHelper::uint_param(balance_of, &params, 0).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidParams);
+    assert_eq!(
+        error.message,
+        "invalid argument of balanceOf: expected uint256"
+    );
+    // Missing arguments are reported the same way.
+    let error = CallsHelper::address_param(balance_of, &params, 1).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidParams);
+}
+
+/// Checks that NFT IDs above the configured ceiling are rejected before the storage lookup.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_max_token_id() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let config = Web3Config {
+        nft_max_token_id: Some(65543),
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let contract = ethabi::Contract::load(NFT_FACTORY_ABI.as_bytes())?;
+    let owner_of = contract.function("ownerOf")?;
+
+    // The ID is rejected by the helper itself, so there is nothing to look up.
+    assert_eq!(
+        calls_helper
+            .extract_token_id(owner_of, &[Token::Uint(U256::from(65543))])
+            .unwrap(),
+        Some(TokenId(65543))
+    );
+    assert_eq!(
+        calls_helper
+            .extract_token_id(owner_of, &[Token::Uint(U256::from(65544))])
+            .unwrap(),
+        None
+    );
+
+    // NFT 65544 exists, but it's above the ceiling.
+    // `ownerOf(uint256)`
+    let mut data = vec![0x63, 0x52, 0x21, 0x1e];
+    data.extend(ethabi::encode(&[Token::Uint(U256::from(65544))]));
+    let error = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.message,
+        "execution reverted: ERC721: owner query for nonexistent token"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that each call failure is reported with its own JSON-RPC error code.
+fn call_error_codes() {
+    let errors = [
+        (
+            CallError::StorageUnavailable,
+            ErrorCode::ServerError(-32002),
+            "storage is unavailable",
+        ),
+        (
+            CallError::TokenNotFound,
+            ErrorCode::ServerError(-32001),
+            "token not found",
+        ),
+        (
+            CallError::DecodeFailed {
+                function: "balanceOf".to_string(),
+                expected: "address",
+            },
+            ErrorCode::InvalidParams,
+            "invalid argument of balanceOf: expected address",
+        ),
+        (
+            CallError::UnsupportedFunction("foo".to_string()),
+            ErrorCode::ServerError(-32004),
+            "function foo is not implemented",
+        ),
+        (
+            CallError::BalanceOverflow("1".to_string()),
+            ErrorCode::ServerError(-32005),
+            "balance overflow: 1 doesn't fit into uint256",
+        ),
+    ];
+    for (call_error, code, message) in errors.iter().cloned() {
+        let error = Error::from(call_error);
+        assert_eq!(error.code, code);
+        assert_eq!(error.message, message);
+        assert_eq!(error.data, None);
+    }
+    for (i, (first, ..)) in errors.iter().enumerate() {
+        for (second, ..) in &errors[i + 1..] {
+            assert_ne!(first.code(), second.code());
+        }
+    }
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that `tokenURI` isn't built for content hashes of unexpected length.
+fn token_uri_content_hash_length() {
+    let calls_helper = CallsHelper::new(&Web3Config::from_env(), Duration::from_secs(1)).unwrap();
+    let error = calls_helper.token_uri(&[0xaa; 31]).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InternalError);
+    assert_eq!(
+        error.message,
+        "NFT content hash must be 32 bytes long, got 31"
+    );
+    assert!(calls_helper.token_uri(&[0xaa; 32]).is_ok());
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that `tokenURI` in the data format embeds the ERC721 metadata JSON.
+fn token_uri_data_format() {
+    let nft = NFT::new(
+        TokenId(65542),
+        7,
+        AccountId(5),
+        H160::from_str("e1faB3eFD74A77C23B426c302D96372140FF7d0C").unwrap(),
+        H160::random(),
+        None,
+        H256::from_str("218145f24cb870cc72ec7f0cc734b86f3e9a744666282f99023f022be77aaea6").unwrap(),
+    );
+    let mut config = Web3Config::from_env();
+    config.nft_cid_version = CidVersion::V0;
+    config.nft_token_uri_prefix = "ipfs://".to_string();
+
+    config.nft_token_uri_format = TokenUriFormat::Ipfs;
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        calls_helper.nft_token_uri(&nft).unwrap(),
+        "ipfs://QmQbSVaG7DUjQ9ktPtMnSXReJ29XHezBghcxJeZDsGG7wB"
+    );
+
+    config.nft_token_uri_format = TokenUriFormat::Data;
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1)).unwrap();
+    let token_uri = calls_helper.nft_token_uri(&nft).unwrap();
+    let encoded = token_uri
+        .strip_prefix("data:application/json;base64,")
+        .unwrap();
+    let metadata: Value = serde_json::from_slice(&base64::decode(encoded).unwrap()).unwrap();
+    let metadata = metadata.as_object().unwrap();
+    assert_eq!(metadata.len(), 4);
+    assert_eq!(metadata["name"], format!("{} #65542", nft.symbol));
+    assert_eq!(
+        metadata["description"],
+        "zkSync NFT #7 created by 0xe1fab3efd74a77c23b426c302d96372140ff7d0c"
+    );
+    assert_eq!(
+        metadata["image"],
+        "ipfs://QmQbSVaG7DUjQ9ktPtMnSXReJ29XHezBghcxJeZDsGG7wB"
+    );
+    assert_eq!(
+        metadata["attributes"],
+        serde_json::json!([
+            { "trait_type": "creator_id", "value": 5 },
+            { "trait_type": "serial_id", "value": 7 },
+            {
+                "trait_type": "creator_address",
+                "value": "0xe1fab3efd74a77c23b426c302d96372140ff7d0c",
+            },
+        ])
+    );
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that the ERC721 metadata JSON has the same bytes for the same NFT.
+fn erc721_metadata_serialization() {
+    let nft = NFT::new(
+        TokenId(65542),
+        7,
+        AccountId(5),
+        H160::from_str("e1faB3eFD74A77C23B426c302D96372140FF7d0C").unwrap(),
+        H160::random(),
+        None,
+        H256::zero(),
+    );
+    let metadata = CallsHelper::build_erc721_metadata(&nft, "ipfs://QmImage");
+    assert_eq!(
+        metadata,
+        concat!(
+            r#"{"name":"NFT-65542 #65542","#,
+            r#""description":"zkSync NFT #7 created by 0xe1fab3efd74a77c23b426c302d96372140ff7d0c","#,
+            r#""image":"ipfs://QmImage","#,
+            r#""attributes":["#,
+            r#"{"trait_type":"creator_id","value":5},"#,
+            r#"{"trait_type":"serial_id","value":7},"#,
+            r#"{"trait_type":"creator_address","value":"0xe1fab3efd74a77c23b426c302d96372140ff7d0c"}"#,
+            r#"]}"#,
+        )
+    );
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that `bytes32` values are always encoded to a single word.
+fn bytes32_token_padding() {
+    let hash = H256::random();
+    assert_eq!(
+        CallsHelper::bytes32_token(hash.as_bytes()).unwrap(),
+        Token::FixedBytes(hash.as_bytes().to_vec())
+    );
+
+    // The short value is right-padded, as `bytes2(0xabcd)` converted to `bytes32`,
+    // and decoded back as the same `bytes32` value.
+    let token = CallsHelper::bytes32_token(&[0xab, 0xcd]).unwrap();
+    let encoded = ethabi::encode(&[token]);
+    assert_eq!(encoded.len(), 32);
+    let mut expected = [0u8; 32];
+    expected[..2].copy_from_slice(&[0xab, 0xcd]);
+    assert_eq!(encoded, expected);
+    let decoded = ethabi::decode(&[ParamType::FixedBytes(32)], &encoded).unwrap();
+    assert_eq!(decoded[0], Token::FixedBytes(expected.to_vec()));
+
+    let error = CallsHelper::bytes32_token(&[0u8; 33]).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InternalError);
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that ERC721 `Transfer` logs have the layout of the canonical event.
+fn erc721_transfer_log() {
+    let logs_helper = LogsHelper::new(&Web3Config::from_env(), Duration::from_secs(1)).unwrap();
+    let common_data = CommonLogData {
+        block_hash: Some(H256::random()),
+        block_number: Some(U64::from(1)),
+        transaction_hash: H256::random(),
+        transaction_index: Some(U64::from(0)),
+    };
+    let from = H160::random();
+    let to = H160::random();
+    let mut index = U256::from(1);
+    let log = logs_helper.erc721_transfer(TokenId(65544), from, to, common_data, &mut index);
+
+    assert_eq!(log.address, H160::from_str(NFT_FACTORY_ADDRESS).unwrap());
+    // `keccak256("Transfer(address,address,uint256)")`
+    let topic0 =
+        H256::from_str("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef").unwrap();
+    let mut token_id_topic = [0u8; 32];
+    U256::from(65544).to_big_endian(&mut token_id_topic);
+    assert_eq!(
+        log.topics,
+        vec![
+            topic0,
+            H256::from_slice(&ethabi::encode(&[Token::Address(from)])),
+            H256::from_slice(&ethabi::encode(&[Token::Address(to)])),
+            H256::from(token_id_topic),
+        ]
+    );
+    assert!(log.data.0.is_empty());
+    assert_eq!(log.transaction_log_index, Some(U256::from(1)));
+    assert_eq!(index, U256::from(2));
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that ERC4906 `MetadataUpdate` logs have the layout of the canonical event.
+fn erc4906_metadata_update_log() {
+    let logs_helper = LogsHelper::new(&Web3Config::from_env(), Duration::from_secs(1)).unwrap();
+    let common_data = CommonLogData {
+        block_hash: Some(H256::random()),
+        block_number: Some(U64::from(1)),
+        transaction_hash: H256::random(),
+        transaction_index: Some(U64::from(0)),
+    };
+    let mut index = U256::from(1);
+    let log = logs_helper.erc4906_metadata_update(TokenId(65544), common_data, &mut index);
+
+    assert_eq!(log.address, H160::from_str(NFT_FACTORY_ADDRESS).unwrap());
+    // `keccak256("MetadataUpdate(uint256)")`
+    let topic0 =
+        H256::from_str("f8e1a15aba9398e019f0b49df1a4fde98ee17ae345cb5f6b5e2c27f5033e8ce7").unwrap();
+    assert_eq!(log.topics, vec![topic0]);
+    assert_eq!(
+        log.data.0,
+        hex::decode("0000000000000000000000000000000000000000000000000000000000010008").unwrap()
+    );
+    assert_eq!(log.transaction_log_index, Some(U256::from(1)));
+    assert_eq!(index, U256::from(2));
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that numbers exceeding `U256::MAX` are reported instead of being truncated.
+fn u256_overflow() {
+    let max = BigUint::from_bytes_be(&[0xff; 32]);
+    assert_eq!(try_u256_from_biguint(&max), Some(U256::max_value()));
+    assert_eq!(u256_from_biguint(max.clone()), U256::max_value());
+    assert_eq!(
+        try_u256_from_biguint(&BigUint::from(1234u32)),
+        Some(U256::from(1234))
+    );
+    assert_eq!(
+        try_u256_from_biguint(&BigUint::from(0u32)),
+        Some(U256::zero())
+    );
+
+    let overflow = max + 1u32;
+    assert_eq!(try_u256_from_biguint(&overflow), None);
+    let error = Error::from(CallError::BalanceOverflow(overflow.to_string()));
+    assert_eq!(error.code, ErrorCode::ServerError(-32005));
+    assert_eq!(
+        error.message,
+        format!("balance overflow: {} doesn't fit into uint256", overflow)
+    );
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that selectors are the first 4 bytes of the signature hash.
+fn selector_for() {
+    assert_eq!(
+        CallsHelper::selector_for("balanceOf(address)"),
+        [0x70, 0xa0, 0x82, 0x31]
+    );
+    assert_eq!(
+        CallsHelper::selector_for("ownerOf(uint256)"),
+        [0x63, 0x52, 0x21, 0x1e]
+    );
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that helpers are constructed from the embedded ABIs without any filesystem access.
+fn embedded_abis() {
+    assert!(std::env::var(ABI_DIR_ENV_VAR).is_err());
+    let calls_helper = CallsHelper::new(&Web3Config::from_env(), Duration::from_secs(1)).unwrap();
+    LogsHelper::new(&Web3Config::from_env(), Duration::from_secs(1)).unwrap();
+
+    let token_address = H160::random();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    // `balanceOf(address)`
+    assert!(calls_helper.has_function(token_address, [0x70, 0xa0, 0x82, 0x31]));
+    // `decimals()`
+    assert!(calls_helper.has_function(token_address, [0x31, 0x3c, 0xe5, 0x67]));
+    // `ownerOf(uint256)`
+    assert!(calls_helper.has_function(nft_factory_address, [0x63, 0x52, 0x21, 0x1e]));
+    // `tokenURI(uint256)`
+    assert!(calls_helper.has_function(nft_factory_address, [0xc8, 0x7b, 0x56, 0xdd]));
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that ABIs are loaded from the given directory, which must contain all of the files.
+fn abi_dir() {
+    let config = Web3Config::from_env();
+    let dir = std::env::temp_dir().join(format!("web3-abi-{:x}", H160::random()));
+    assert!(CallsHelper::with_abi_dir(&config, Duration::from_secs(1), Some(&dir)).is_err());
+
+    std::fs::create_dir(&dir).unwrap();
+    // The ERC20 fixture has nothing but `balanceOf`.
+    let erc20_abi = r#"[{
+        "inputs": [{"internalType": "address", "name": "account", "type": "address"}],
+        "name": "balanceOf",
+        "outputs": [{"internalType": "uint256", "name": "", "type": "uint256"}],
+        "stateMutability": "view",
+        "type": "function"
+    }]"#;
+    std::fs::write(dir.join("ERC20.json"), erc20_abi).unwrap();
+    std::fs::write(dir.join("NFTFactory.json"), NFT_FACTORY_ABI).unwrap();
+    std::fs::write(dir.join("ERC1155.json"), ERC1155_ABI).unwrap();
+    let error = CallsHelper::with_abi_dir(&config, Duration::from_secs(1), Some(&dir))
+        .err()
+        .unwrap();
+    assert!(
+        error
+            .to_string()
+            .ends_with("doesn't contain Multicall3.json"),
+        "{}",
+        error
+    );
+
+    std::fs::write(dir.join("Multicall3.json"), MULTICALL3_ABI).unwrap();
+    let calls_helper = CallsHelper::with_abi_dir(&config, Duration::from_secs(1), Some(&dir));
+    std::fs::remove_dir_all(&dir).unwrap();
+    let calls_helper = calls_helper.unwrap();
+    let token_address = H160::random();
+    // `balanceOf(address)`
+    assert!(calls_helper.has_function(token_address, [0x70, 0xa0, 0x82, 0x31]));
+    // `decimals()` is in the embedded ABI only.
+    assert!(!calls_helper.has_function(token_address, [0x31, 0x3c, 0xe5, 0x67]));
+    // `ownerOf(uint256)`
+    assert!(calls_helper.has_function(
+        H160::from_str(NFT_FACTORY_ADDRESS).unwrap(),
+        [0x63, 0x52, 0x21, 0x1e]
+    ));
+
+    // Without the directory the embedded ABIs are used.
+    let calls_helper = CallsHelper::with_abi_dir(&config, Duration::from_secs(1), None).unwrap();
+    assert!(calls_helper.has_function(token_address, [0x31, 0x3c, 0xe5, 0x67]));
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that ABI functions without an emulation fail the start only in the strict mode.
+fn strict_abi() {
+    let config = Web3Config::from_env();
+    let strict_config = Web3Config {
+        call_strict_abi: true,
+        ..config.clone()
+    };
+    // All of the embedded ABI functions are emulated.
+    CallsHelper::with_abi_dir(&strict_config, Duration::from_secs(1), None).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("web3-abi-{:x}", H160::random()));
+    std::fs::create_dir(&dir).unwrap();
+    let mut erc20_abi: Vec<Value> = serde_json::from_str(ERC20_ABI).unwrap();
+    erc20_abi.push(serde_json::json!({
+        "inputs": [{"internalType": "uint256", "name": "amount", "type": "uint256"}],
+        "name": "mint",
+        "outputs": [],
+        "stateMutability": "nonpayable",
+        "type": "function"
+    }));
+    std::fs::write(
+        dir.join("ERC20.json"),
+        serde_json::to_string(&erc20_abi).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(dir.join("NFTFactory.json"), NFT_FACTORY_ABI).unwrap();
+    std::fs::write(dir.join("ERC1155.json"), ERC1155_ABI).unwrap();
+    std::fs::write(dir.join("Multicall3.json"), MULTICALL3_ABI).unwrap();
+
+    let strict_result =
+        CallsHelper::with_abi_dir(&strict_config, Duration::from_secs(1), Some(&dir));
+    let lenient_result = CallsHelper::with_abi_dir(&config, Duration::from_secs(1), Some(&dir));
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(
+        strict_result.err().unwrap().to_string(),
+        "ABI functions without an emulation: ERC20.mint"
+    );
+    assert!(lenient_result.is_ok());
+}
+
+/// Collects the messages of the WARN events.
+struct WarningCollector(Arc<Mutex<Vec<String>>>);
+
+impl Subscriber for WarningCollector {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() == Level::WARN
+    }
+
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        struct MessageVisitor(Option<String>);
+
+        impl Visit for MessageVisitor {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = Some(format!("{:?}", value));
+                }
+            }
+        }
+
+        let mut visitor = MessageVisitor(None);
+        event.record(&mut visitor);
+        self.0.lock().unwrap().extend(visitor.0);
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that each ABI function without an emulation is logged at startup in the lenient mode.
+fn unimplemented_abi_warnings() {
+    let config = Web3Config {
+        call_strict_abi: false,
+        ..Web3Config::from_env()
+    };
+    let dir = std::env::temp_dir().join(format!("web3-abi-{:x}", H160::random()));
+    std::fs::create_dir(&dir).unwrap();
+    let mut erc20_abi: Vec<Value> = serde_json::from_str(ERC20_ABI).unwrap();
+    for name in ["mint", "burn"] {
+        erc20_abi.push(serde_json::json!({
+            "inputs": [{"internalType": "uint256", "name": "amount", "type": "uint256"}],
+            "name": name,
+            "outputs": [],
+            "stateMutability": "nonpayable",
+            "type": "function"
+        }));
+    }
+    std::fs::write(
+        dir.join("ERC20.json"),
+        serde_json::to_string(&erc20_abi).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(dir.join("NFTFactory.json"), NFT_FACTORY_ABI).unwrap();
+    std::fs::write(dir.join("ERC1155.json"), ERC1155_ABI).unwrap();
+    std::fs::write(dir.join("Multicall3.json"), MULTICALL3_ABI).unwrap();
+
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let result = subscriber::with_default(WarningCollector(warnings.clone()), || {
+        CallsHelper::with_abi_dir(&config, Duration::from_secs(1), Some(&dir))
+    });
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(result.is_ok());
+    let abi_warnings: Vec<String> = warnings
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|message| message.starts_with("ABI function"))
+        .cloned()
+        .collect();
+    assert_eq!(
+        abi_warnings,
+        [
+            "ABI function ERC20.burn has no emulation, its calls fail",
+            "ABI function ERC20.mint has no emulation, its calls fail",
+        ]
+    );
+}
+
+/// Checks that ERC20 metadata is loaded from the storage only once.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_metadata_cache() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(60))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(16).into())
+        .await?
+        .unwrap();
+
+    storage
+        .tokens_schema()
+        .store_token_name(token.id, "Golem")
+        .await?;
+    // `name()`
+    let data = vec![0x06, 0xfd, 0xde, 0x03];
+    let resp_data = calls_helper
+        .execute(&mut storage, token.address, data.clone(), None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_string().unwrap(), "Golem");
+
+    // The second call must be served from the cache, so the updated name is not visible.
+    storage
+        .tokens_schema()
+        .store_token_name(token.id, "Golem Network Token")
+        .await?;
+    let resp_data = calls_helper
+        .execute(&mut storage, token.address, data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_string().unwrap(), "Golem");
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Checks that overloaded functions are dispatched separately and colliding ones are rejected.
+fn function_selector_collisions() {
+    let balance_of = |inputs: &str| {
+        format!(
+            r#"{{
+                "inputs": [{}],
+                "name": "balanceOf",
+                "outputs": [{{"internalType": "uint256", "name": "", "type": "uint256"}}],
+                "stateMutability": "view",
+                "type": "function"
+            }}"#,
+            inputs
+        )
+    };
+    let owner = r#"{"internalType": "address", "name": "owner", "type": "address"}"#;
+    let id = r#"{"internalType": "uint256", "name": "id", "type": "uint256"}"#;
+
+    let overloaded_abi = format!(
+        "[{}, {}]",
+        balance_of(owner),
+        balance_of(&format!("{}, {}", owner, id))
+    );
+    let contract = ethabi::Contract::load(overloaded_abi.as_bytes()).unwrap();
+    let functions =
+        CallsHelper::function_by_selector(CallsHelper::contract_functions(contract)).unwrap();
+    assert_eq!(functions.len(), 2);
+
+    let colliding_abi = format!("[{}, {}]", balance_of(owner), balance_of(owner));
+    let contract = ethabi::Contract::load(colliding_abi.as_bytes()).unwrap();
+    assert!(CallsHelper::function_by_selector(CallsHelper::contract_functions(contract)).is_err());
+}
+
+/// Checks that calls to every configured zkSync proxy are served by the NFT functions.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn multiple_zksync_proxies() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let proxy_addresses = vec![
+        H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap(),
+        H160::from_str("1000000000000000000000000000000000000001").unwrap(),
+    ];
+    let config = Web3Config {
+        zksync_proxy_addresses: proxy_addresses.clone(),
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+
+    for proxy_address in &proxy_addresses {
+        // `supportsInterface(bytes4)` with the ERC721 interface ID.
+        let mut data = vec![0x01, 0xff, 0xc9, 0xa7];
+        data.extend(ethabi::encode(&[Token::FixedBytes(vec![
+            0x80, 0xac, 0x58, 0xcd,
+        ])]));
+        let resp_data = calls_helper
+            .execute(&mut storage, *proxy_address, data, None, None)
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::Bool], &resp_data).unwrap();
+        assert!(outputs[0].clone().into_bool().unwrap());
+
+        // `isApprovedForAll(address,address)`
+        let mut data = vec![0xe9, 0x85, 0xe9, 0xc5];
+        data.extend(ethabi::encode(&[
+            Token::Address(H160::random()),
+            Token::Address(*proxy_address),
+        ]));
+        let resp_data = calls_helper
+            .execute(&mut storage, *proxy_address, data, None, None)
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::Bool], &resp_data).unwrap();
+        assert!(outputs[0].clone().into_bool().unwrap());
+    }
+
+    Ok(())
+}
+
+/// Checks that the calls of the batch are executed independently and in order.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn execute_batch() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+
+    // `ownerOf(uint256)`
+    let owner_of = |token_id: U256| {
+        let mut data = vec![0x63, 0x52, 0x21, 0x1e];
+        data.extend(ethabi::encode(&[Token::Uint(token_id)]));
+        data
+    };
+    let calls = vec![
+        (nft_factory_address, owner_of(U256::from(nft.id.0))),
+        (nft_factory_address, vec![0xff, 0xff, 0xff, 0xff]),
+        (nft_factory_address, owner_of(U256::max_value())),
+    ];
+    let expected_owner = calls_helper
+        .execute(&mut storage, calls[0].0, calls[0].1.clone(), None, None)
+        .await
+        .unwrap();
+
+    let results = calls_helper
+        .execute_batch(&mut storage, calls, None, None, None)
+        .await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap(), &expected_owner);
+    assert_eq!(
+        results[1].as_ref().unwrap_err().code,
+        ErrorCode::InvalidParams
+    );
+    let error = results[2].as_ref().unwrap_err();
+    assert_eq!(error.code, ErrorCode::ServerError(3));
+    assert_eq!(
+        error.message,
+        "execution reverted: ERC721: owner query for nonexistent token"
+    );
+
+    Ok(())
+}
+
+/// Checks that `aggregate3` of the Multicall3 executes all the sub-calls.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn multicall3_aggregate3() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let multicall3_address = H160::from_str(MULTICALL3_ADDRESS).unwrap();
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let address = storage
+        .chain()
+        .account_schema()
+        .account_address_by_id(AccountId(3))
+        .await?
+        .unwrap();
+    // The second account doesn't exist, so it has zero balance.
+    let addresses = vec![address, H160::random()];
+
+    // `balanceOf(address)`
+    let balance_of = |address: H160| {
+        let mut data = vec![0x70, 0xa0, 0x82, 0x31];
+        data.extend(ethabi::encode(&[Token::Address(address)]));
+        data
+    };
+    let mut expected_results = Vec::new();
+    for address in &addresses {
+        let result = calls_helper
+            .execute(
+                &mut storage,
+                token.address,
+                balance_of(*address),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        expected_results.push(result);
+    }
+
+    // `aggregate3((address,bool,bytes)[])`
+    let calls = addresses
+        .iter()
+        .map(|address| {
+            Token::Tuple(vec![
+                Token::Address(token.address),
+                Token::Bool(false),
+                Token::Bytes(balance_of(*address)),
+            ])
+        })
+        .collect();
+    let mut data = vec![0x82, 0xad, 0x56, 0xcb];
+    data.extend(ethabi::encode(&[Token::Array(calls)]));
+    let resp_data = calls_helper
+        .execute(&mut storage, multicall3_address, data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])))],
+        &resp_data,
+    )
+    .unwrap();
+    let results = outputs[0].clone().into_array().unwrap();
+    assert_eq!(results.len(), expected_results.len());
+    for (result, expected) in results.into_iter().zip(expected_results) {
+        let result = result.into_tuple().unwrap();
+        assert!(result[0].clone().into_bool().unwrap());
+        assert_eq!(result[1].clone().into_bytes().unwrap(), expected);
+        let balance = ethabi::decode(&[ParamType::Uint(256)], &expected).unwrap();
+        assert!(balance[0].clone().into_uint().is_some());
+    }
+
+    Ok(())
+}
+
+/// Checks that owners of several NFTs are loaded at once, including by `aggregate3`.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_owners_batch() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let multicall3_address = H160::from_str(MULTICALL3_ADDRESS).unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    let missing_id = TokenId(NFT_TOKEN_ID_VAL - 1);
+    let token_ids = vec![missing_id, nft.id, missing_id, nft.id];
+
+    // `ownerOf(uint256)`
+    let owner_of = |token_id: TokenId| {
+        let mut data = vec![0x63, 0x52, 0x21, 0x1e];
+        data.extend(ethabi::encode(&[Token::Uint(U256::from(token_id.0))]));
+        data
+    };
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            owner_of(nft.id),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let owner = ethabi::decode(&[ParamType::Address], &resp_data).unwrap()[0]
+        .clone()
+        .into_address()
+        .unwrap();
+
+    let owners = calls_helper.owners_of(&mut storage, &token_ids).await?;
+    assert_eq!(owners, vec![None, Some(owner), None, Some(owner)]);
+
+    // `aggregate3((address,bool,bytes)[])`
+    let calls = token_ids
+        .iter()
+        .map(|token_id| {
+            Token::Tuple(vec![
+                Token::Address(nft_factory_address),
+                Token::Bool(true),
+                Token::Bytes(owner_of(*token_id)),
+            ])
+        })
+        .collect();
+    let mut data = vec![0x82, 0xad, 0x56, 0xcb];
+    data.extend(ethabi::encode(&[Token::Array(calls)]));
+    let resp_data = calls_helper
+        .execute(&mut storage, multicall3_address, data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])))],
+        &resp_data,
+    )
+    .unwrap();
+    let results = outputs[0].clone().into_array().unwrap();
+    assert_eq!(results.len(), token_ids.len());
+    let mut expected_revert = vec![0x08, 0xc3, 0x79, 0xa0];
+    expected_revert.extend(ethabi::encode(&[Token::String(
+        "ERC721: owner query for nonexistent token".to_string(),
+    )]));
+    for (result, expected_owner) in results.into_iter().zip(owners) {
+        let result = result.into_tuple().unwrap();
+        let return_data = result[1].clone().into_bytes().unwrap();
+        match expected_owner {
+            Some(owner) => {
+                assert!(result[0].clone().into_bool().unwrap());
+                assert_eq!(return_data, ethabi::encode(&[Token::Address(owner)]));
+            }
+            None => {
+                assert!(!result[0].clone().into_bool().unwrap());
+                assert_eq!(return_data, expected_revert);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `eth_call` at `latest` is executed as the call without a block,
+/// so `aggregate3` of the `ownerOf` calls gets the batched latest owners.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_owners_batch_latest() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let multicall3_address = H160::from_str(MULTICALL3_ADDRESS).unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let token_ids = vec![TokenId(65544), TokenId(NFT_TOKEN_ID_VAL - 1)];
+
+    let block = calls_helper
+        .resolve_call_block(&mut storage, Some(BlockTag::Latest))
+        .await
+        .unwrap();
+    assert_eq!(block, None);
+    let block = calls_helper
+        .resolve_call_block(&mut storage, Some(BlockTag::Earliest))
+        .await
+        .unwrap();
+    assert_eq!(block, Some(BlockNumber(1)));
+
+    // `aggregate3((address,bool,bytes)[])` of `ownerOf(uint256)`
+    let calls = token_ids
+        .iter()
+        .map(|token_id| {
+            let mut data = vec![0x63, 0x52, 0x21, 0x1e];
+            data.extend(ethabi::encode(&[Token::Uint(U256::from(token_id.0))]));
+            Token::Tuple(vec![
+                Token::Address(nft_factory_address),
+                Token::Bool(true),
+                Token::Bytes(data),
+            ])
+        })
+        .collect();
+    let mut data = vec![0x82, 0xad, 0x56, 0xcb];
+    data.extend(ethabi::encode(&[Token::Array(calls)]));
+
+    let mut responses = Vec::new();
+    for block in [None, Some("latest")] {
+        let fut = {
+            let (client, server) = local_client().await?;
+            let mut req = Map::new();
+            req.insert(
+                "to".to_string(),
+                Value::String(format!("{:#?}", multicall3_address)),
+            );
+            req.insert(
+                "data".to_string(),
+                Value::String(format!("0x{}", hex::encode(&data))),
+            );
+            let mut params = vec![Value::Object(req)];
+            params.extend(block.map(|block| Value::String(block.to_string())));
+            join(
+                client.call_method("eth_call", Params::Array(params)),
+                server,
+            )
+        };
+        let resp_data = fut.await.0.unwrap();
+        responses.push(serde_json::from_value::<String>(resp_data).unwrap());
+    }
+    assert_eq!(responses[0], responses[1]);
+
+    let outputs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])))],
+        &hex::decode(responses[1].strip_prefix("0x").unwrap()).unwrap(),
+    )
+    .unwrap();
+    let results = outputs[0].clone().into_array().unwrap();
+    let owners = calls_helper.owners_of(&mut storage, &token_ids).await?;
+    assert_eq!(
+        results[0].clone().into_tuple().unwrap(),
+        vec![
+            Token::Bool(true),
+            Token::Bytes(ethabi::encode(&[Token::Address(owners[0].unwrap())]))
+        ]
+    );
+    assert_eq!(owners[1], None);
+    assert_eq!(
+        results[1].clone().into_tuple().unwrap()[0],
+        Token::Bool(false)
+    );
+
+    Ok(())
+}
+
+/// Checks that the permit nonce of any ERC20 holder is zero.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_permit_nonces() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let address = storage
+        .chain()
+        .account_schema()
+        .account_address_by_id(AccountId(3))
+        .await?
+        .unwrap();
+
+    for owner in [address, H160::random()] {
+        // `nonces(address)`
+        let mut data = vec![0x7e, 0xce, 0xbe, 0x00];
+        data.extend(ethabi::encode(&[Token::Address(owner)]));
+        let resp_data = calls_helper
+            .execute(&mut storage, token.address, data, None, None)
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+        assert_eq!(outputs[0].clone().into_uint().unwrap(), U256::zero());
+    }
+
+    Ok(())
+}
+
+/// Checks the EIP-2612 permit type hash and the EIP-712 domain separator of ERC20 tokens.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_permit_domain() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    // `PERMIT_TYPEHASH()`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            token.address,
+            vec![0x30, 0xad, 0xf8, 0x1f],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        hex::encode(resp_data),
+        "6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c9"
+    );
+
+    // `name()`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            token.address,
+            vec![0x06, 0xfd, 0xde, 0x03],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let name = ethabi::decode(&[ParamType::String], &resp_data).unwrap()[0]
+        .clone()
+        .into_string()
+        .unwrap();
+    // `DOMAIN_SEPARATOR()`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            token.address,
+            vec![0x36, 0x44, 0xe5, 0x15],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    // `keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)")`
+    let domain_type_hash =
+        hex::decode("8b73c3c69bb8fe3d512ecc4cf759cc79239f7b179b0ffacaa9a75d522b39400f").unwrap();
+    let expected_separator = tiny_keccak::keccak256(&ethabi::encode(&[
+        Token::FixedBytes(domain_type_hash),
+        Token::FixedBytes(tiny_keccak::keccak256(name.as_bytes()).to_vec()),
+        Token::FixedBytes(tiny_keccak::keccak256(b"1").to_vec()),
+        Token::Uint(U256::from(cfg.config.api.web3.chain_id)),
+        Token::Address(token.address),
+    ]));
+    assert_eq!(resp_data, expected_separator.to_vec());
+
+    Ok(())
+}
+
+/// Checks that `contentHashCid` returns the plain IPFS CID regardless of the `tokenURI` prefix.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn content_hash_cid() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let config = Web3Config {
+        nft_cid_version: CidVersion::V0,
+        nft_content_multihash: Multihash::Sha256,
+        nft_token_uri_prefix: "https://gateway.example.com/ipfs/".to_string(),
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    // `contentHashCid(uint256)`
+    let mut data = vec![0xf5, 0xb1, 0xd5, 0x6b];
+    data.extend(ethabi::encode(&[Token::Uint(U256::from(nft.id.0))]));
+    let resp_data = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_string().unwrap(),
+        format!(
+            "ipfs://{}",
+            CallsHelper::ipfs_cid(Multihash::Sha256, nft.content_hash.as_bytes()).unwrap()
+        )
+    );
+
+    let mut data = vec![0xf5, 0xb1, 0xd5, 0x6b];
+    data.extend(ethabi::encode(&[Token::Uint(U256::from(
+        MIN_NFT_TOKEN_ID - 1,
+    ))]));
+    let error = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.message,
+        "execution reverted: content CID query for nonexistent token"
+    );
+
+    Ok(())
+}
+
+/// Checks that `native_balance` returns the ETH balance of the account at the block.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn native_balance() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let last_block = storage
+        .chain()
+        .block_schema()
+        .get_last_verified_confirmed_block()
+        .await?;
+
+    let funded_address = H160::from_str("09d1ef5f45cfa30225edff40cebf657b4226b27b").unwrap();
+    let expected_balance = storage
+        .chain()
+        .account_schema()
+        .get_account_balance_for_block(funded_address, last_block, TokenId(0))
+        .await?;
+    let balance = calls_helper
+        .native_balance(&mut storage, funded_address, last_block)
+        .await
+        .unwrap();
+    assert_eq!(balance, u256_from_biguint(expected_balance));
+
+    let empty_address = H160::from_low_u64_be(0xdead);
+    let balance = calls_helper
+        .native_balance(&mut storage, empty_address, last_block)
+        .await
+        .unwrap();
+    assert_eq!(balance, U256::zero());
+
+    Ok(())
+}
+
+/// Checks that `warm_up` preloads the token metadata used by `execute`.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn warm_up() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    assert!(!calls_helper.is_token_metadata_cached(token.address).await);
+
+    // NFTs are not ERC20 tokens, so there is nothing to preload for them.
+    calls_helper
+        .warm_up(&mut storage, &[token.id, nft.id])
+        .await
+        .unwrap();
+    assert!(calls_helper.is_token_metadata_cached(token.address).await);
+    assert!(!calls_helper.is_token_metadata_cached(nft.address).await);
+
+    // `symbol()` is served from the preloaded metadata.
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            token.address,
+            vec![0x95, 0xd8, 0x9b, 0x41],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_string().unwrap(), token.symbol);
+
+    Ok(())
+}
+
+/// Checks that the invalidated token metadata is loaded from the storage by the next call.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn invalidate_token_metadata() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let other_token = storage
+        .tokens_schema()
+        .get_token(TokenId(2).into())
+        .await?
+        .unwrap();
+
+    // The name is only changed in the rolled back transaction.
+    let mut transaction = storage.start_transaction().await?;
+    for address in [token.address, other_token.address] {
+        calls_helper
+            .execute(
+                &mut transaction,
+                address,
+                vec![0x06, 0xfd, 0xde, 0x03],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+    }
+    transaction
+        .tokens_schema()
+        .store_token_name(token.id, "Renamed Token")
+        .await?;
+
+    // `name()`
+    let name = |resp_data: Vec<u8>| {
+        let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+        outputs[0].clone().into_string().unwrap()
+    };
+    let resp_data = calls_helper
+        .execute(
+            &mut transaction,
+            token.address,
+            vec![0x06, 0xfd, 0xde, 0x03],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_ne!(name(resp_data), "Renamed Token");
+
+    calls_helper.invalidate_token(token.address).await;
+    assert!(!calls_helper.is_token_metadata_cached(token.address).await);
+    assert!(
+        calls_helper
+            .is_token_metadata_cached(other_token.address)
+            .await
+    );
+    let resp_data = calls_helper
+        .execute(
+            &mut transaction,
+            token.address,
+            vec![0x06, 0xfd, 0xde, 0x03],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(name(resp_data), "Renamed Token");
+    assert!(calls_helper.is_token_metadata_cached(token.address).await);
+
+    calls_helper.invalidate_all().await;
+    assert!(!calls_helper.is_token_metadata_cached(token.address).await);
+    assert!(
+        !calls_helper
+            .is_token_metadata_cached(other_token.address)
+            .await
+    );
+
+    Ok(())
+}
+
+/// Checks that `getOwnerTokens` returns all NFTs of the owner page by page.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn get_owner_tokens() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let owner_id = storage
+        .chain()
+        .account_schema()
+        .get_nft_owner(TokenId(65544))
+        .await?
+        .unwrap();
+    let owner = storage
+        .chain()
+        .account_schema()
+        .account_address_by_id(owner_id)
+        .await?
+        .unwrap();
+    let balance = storage
+        .chain()
+        .account_schema()
+        .get_account_nft_balance(owner)
+        .await?;
+    // `getOwnerTokens(address,uint256,uint256)`
+    let get_owner_tokens = |offset: u32, limit: u32| {
+        let mut data = vec![0x1f, 0x0c, 0xc8, 0x74];
+        data.extend(ethabi::encode(&[
+            Token::Address(owner),
+            Token::Uint(U256::from(offset)),
+            Token::Uint(U256::from(limit)),
+        ]));
+        data
+    };
+
+    // Pages of a single token, so the owner has more tokens than one page.
+    let mut token_ids = Vec::new();
+    for offset in 0..=balance {
+        let resp_data = calls_helper
+            .execute(
+                &mut storage,
+                nft_factory_address,
+                get_owner_tokens(offset, 1),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(
+            &[ParamType::Array(Box::new(ParamType::Uint(256)))],
+            &resp_data,
+        )
+        .unwrap();
+        let page = outputs[0].clone().into_array().unwrap();
+        // The page after the last token is empty.
+        assert_eq!(page.len(), if offset < balance { 1 } else { 0 });
+        token_ids.extend(page.into_iter().map(|id| id.into_uint().unwrap()));
+    }
+    assert!(token_ids.contains(&U256::from(65544)));
+    assert!(token_ids.windows(2).all(|pair| pair[0] < pair[1]));
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            get_owner_tokens(0, balance),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Uint(256)))],
+        &resp_data,
+    )
+    .unwrap();
+    assert_eq!(
+        outputs[0].clone().into_array().unwrap(),
+        token_ids.into_iter().map(Token::Uint).collect::<Vec<_>>()
+    );
+
+    let error = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            get_owner_tokens(0, 101),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.message,
+        "execution reverted: getOwnerTokens: limit exceeds 100"
+    );
+
+    Ok(())
+}
+
+/// Checks that `explain` reports the contract, the function and the arguments of the call.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn explain_call() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+
+    // `ownerOf(uint256)`
+    let mut data = vec![0x63, 0x52, 0x21, 0x1e];
+    data.extend(ethabi::encode(&[Token::Uint(U256::from(65544))]));
+    let explanation = calls_helper
+        .explain(&mut storage, nft_factory_address, &data)
+        .await
+        .unwrap();
+    assert_eq!(
+        explanation,
+        CallExplanation {
+            contract: Some(ContractKind::Nft),
+            function: "ownerOf".to_string(),
+            params: vec!["65544".to_string()],
+        }
+    );
+
+    let explanation = calls_helper
+        .explain(&mut storage, token.address, &[0xaa, 0xbb, 0xcc, 0xdd])
+        .await
+        .unwrap();
+    assert_eq!(explanation.contract, Some(ContractKind::Erc20));
+    assert_eq!(explanation.function, "unknown selector");
+
+    let explanation = calls_helper
+        .explain(&mut storage, H160::zero(), &data)
+        .await
+        .unwrap();
+    assert_eq!(explanation.contract, None);
+
+    Ok(())
+}
+
+/// Checks that the `eth_call` sender is accepted and doesn't change the results.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn call_sender() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    // `balanceOf(address)`
+    let mut balance_data = vec![0x70, 0xa0, 0x82, 0x31];
+    balance_data.extend(ethabi::encode(&[Token::Address(nft.creator_address)]));
+    // `ownerOf(uint256)`
+    let mut owner_data = vec![0x63, 0x52, 0x21, 0x1e];
+    owner_data.extend(ethabi::encode(&[Token::Uint(U256::from(nft.id.0))]));
+    let calls = [
+        (token.address, balance_data.clone()),
+        (nft_factory_address, balance_data),
+        (nft_factory_address, owner_data),
+    ];
+    for (to, data) in calls {
+        let expected = calls_helper
+            .execute(&mut storage, to, data.clone(), None, None)
+            .await
+            .unwrap();
+        for from in [nft.creator_address, H160::random()] {
+            let resp_data = calls_helper
+                .execute(&mut storage, to, data.clone(), None, Some(from))
+                .await
+                .unwrap();
+            assert_eq!(resp_data, expected);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `getContractType` classifies the addresses the same way the calls are routed.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn get_contract_type() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+
+    for (address, expected_type) in [
+        (token.address, 1u8),
+        (nft_factory_address, 2),
+        (H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap(), 2),
+        // Calls to the NFT address itself aren't emulated.
+        (nft.address, 0),
+        (H160::random(), 0),
+        (H160::zero(), 0),
+    ] {
+        // `getContractType(address)`
+        let mut data = vec![0xe1, 0x1a, 0xa6, 0xa2];
+        data.extend(ethabi::encode(&[Token::Address(address)]));
+        let resp_data = calls_helper
+            .execute(&mut storage, nft_factory_address, data, None, None)
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::Uint(8)], &resp_data).unwrap();
+        assert_eq!(
+            outputs[0].clone().into_uint().unwrap(),
+            U256::from(expected_type),
+            "{:?}",
+            address
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that the oversized call data is rejected before decoding.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn call_max_data_size() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let config = Web3Config {
+        call_max_data_size: 36,
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+
+    // `balanceOf(address)` fits exactly.
+    let mut data = vec![0x70, 0xa0, 0x82, 0x31];
+    data.extend(ethabi::encode(&[Token::Address(H160::random())]));
+    let resp_data = calls_helper
+        .execute(&mut storage, token.address, data.clone(), None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+    assert!(outputs[0].clone().into_uint().unwrap().is_zero());
+
+    // Even the zero address, which is never accessed, and Multicall3 calls are checked.
+    data.push(0);
+    for to in [
+        token.address,
+        H160::zero(),
+        H160::from_str(MULTICALL3_ADDRESS).unwrap(),
+    ] {
+        let error = calls_helper
+            .execute(&mut storage, to, data.clone(), None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidParams);
+        assert_eq!(error.message, "call data exceeds 36 bytes");
+    }
+
+    // The default limit is 128 KiB.
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let error = calls_helper
+        .execute(&mut storage, token.address, vec![0; 131_073], None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidParams);
+
+    Ok(())
+}
+
+/// Checks that `tokenInfo` returns the ERC20 metadata and the logo URI in a single call.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn token_info() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    // `tokenInfo(address)`
+    let token_info = |address: H160| {
+        let mut data = vec![0xf5, 0xda, 0xb7, 0x11];
+        data.extend(ethabi::encode(&[Token::Address(address)]));
+        data
+    };
+    let output_types = [
+        ParamType::String,
+        ParamType::String,
+        ParamType::Uint(8),
+        ParamType::String,
+    ];
+
+    let config = Web3Config {
+        erc20_logo_uri_pattern: Some("https://cdn.example.com/{symbol}/{address}.png".to_string()),
+        ..cfg.config.api.web3.clone()
+    };
+    let expected_logo_uri = format!(
+        "https://cdn.example.com/{}/{:?}.png",
+        token.symbol, token.address
+    );
+    for (config, expected_logo_uri) in [
+        (config, expected_logo_uri),
+        (cfg.config.api.web3.clone(), String::new()),
+    ] {
+        let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+        let resp_data = calls_helper
+            .execute(
+                &mut storage,
+                nft_factory_address,
+                token_info(token.address),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&output_types, &resp_data).unwrap();
+        // The name is the one returned by `name()` of the token.
+        let name_data = calls_helper
+            .execute(
+                &mut storage,
+                token.address,
+                vec![0x06, 0xfd, 0xde, 0x03],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let name = ethabi::decode(&[ParamType::String], &name_data).unwrap();
+        assert_eq!(outputs[0], name[0]);
+        assert_eq!(outputs[1].clone().into_string().unwrap(), token.symbol);
+        assert_eq!(
+            outputs[2].clone().into_uint().unwrap(),
+            U256::from(token.decimals)
+        );
+        assert_eq!(outputs[3].clone().into_string().unwrap(), expected_logo_uri);
+    }
+
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let error = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            token_info(H160::random()),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.message,
+        "execution reverted: tokenInfo query for unknown token"
+    );
+
+    // The pattern without placeholders is rejected.
+    let config = Web3Config {
+        erc20_logo_uri_pattern: Some("https://cdn.example.com/logo.png".to_string()),
+        ..cfg.config.api.web3.clone()
+    };
+    assert!(CallsHelper::new(&config, Duration::from_secs(1)).is_err());
+
+    Ok(())
+}
+
+/// Checks that ERC20 `balanceOf` is zero without a balance row, but storage failures are errors.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_balance_without_row() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let address = storage
+        .chain()
+        .account_schema()
+        .account_address_by_id(AccountId(3))
+        .await?
+        .unwrap();
+    // `balanceOf(address)`
+    let mut data = vec![0x70, 0xa0, 0x82, 0x31];
+    data.extend(ethabi::encode(&[Token::Address(address)]));
+
+    // The token exists only in the rolled back transaction, so nobody has its balance.
+    let mut transaction = storage.start_transaction().await?;
+    let token = zksync_types::Token::new(
+        TokenId(60000),
+        H160::random(),
+        "NOBAL",
+        18,
+        zksync_types::TokenKind::ERC20,
+    );
+    transaction
+        .tokens_schema()
+        .store_token(token.clone())
+        .await?;
+    let resp_data = calls_helper
+        .execute(&mut transaction, token.address, data.clone(), None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+    assert!(outputs[0].clone().into_uint().unwrap().is_zero());
+
+    // Every query fails after the NUL character is rejected, the token metadata is cached,
+    // so the call reaches the storage and reports its failure instead of a zero balance.
+    let invalid_token = zksync_types::Token::new(
+        TokenId(u16::MAX as u32),
+        H160::random(),
+        "\0",
+        18,
+        zksync_types::TokenKind::ERC20,
+    );
+    assert!(transaction
+        .tokens_schema()
+        .store_token(invalid_token)
+        .await
+        .is_err());
+    let error = calls_helper
+        .execute(
+            &mut transaction,
+            token.address,
+            data,
+            Some(BlockNumber(1)),
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::ServerError(-32002));
+
+    Ok(())
+}
+
+/// Checks that `tokenURI` falls back to the base URI only for the NFTs without a content hash.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_base_token_uri() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let config = Web3Config {
+        nft_base_token_uri: Some("https://api.example.com/nft/".to_string()),
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    // `tokenURI(uint256)`
+    let token_uri_data = |token_id: TokenId| {
+        let mut data = vec![0xc8, 0x7b, 0x56, 0xdd];
+        data.extend(ethabi::encode(&[Token::Uint(U256::from(token_id.0))]));
+        data
+    };
+
+    // NFTs with a content hash keep the IPFS link.
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    assert!(!nft.content_hash.is_zero());
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            token_uri_data(nft.id),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_string().unwrap(),
+        calls_helper
+            .content_hash_to_token_uri(nft.content_hash)
+            .unwrap()
+    );
+
+    // The NFT without a content hash exists only in the rolled back transaction.
+    let mut transaction = storage.start_transaction().await?;
+    let nft = NFT::new(
+        TokenId(81235),
+        1,
+        AccountId(1),
+        H160::random(),
+        H160::random(),
+        None,
+        H256::zero(),
+    );
+    let update = (
+        nft.creator_id,
+        AccountUpdate::MintNFT {
+            token: nft.clone(),
+            nonce: Nonce(0),
+        },
+    );
+    transaction
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(1), &[update], 0)
+        .await?;
+    let resp_data = calls_helper
+        .execute(
+            &mut transaction,
+            nft_factory_address,
+            token_uri_data(nft.id),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_string().unwrap(),
+        "https://api.example.com/nft/81235"
+    );
+    // Without the base URI the zero hash is linked as any other one.
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    assert_eq!(
+        calls_helper.nft_token_uri(&nft).unwrap(),
+        calls_helper
+            .content_hash_to_token_uri(H256::zero())
+            .unwrap()
+    );
+
+    Ok(())
+}
+
+/// Checks that the immutable NFT getters are answered from the output cache without the storage.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_output_cache() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    let token_id = ethabi::encode(&[Token::Uint(U256::from(nft.id.0))]);
+    // `tokenURI(uint256)`
+    let token_uri_data = [vec![0xc8, 0x7b, 0x56, 0xdd], token_id.clone()].concat();
+    // `ownerOf(uint256)`
+    let owner_of_data = [vec![0x63, 0x52, 0x21, 0x1e], token_id].concat();
+
+    let mut transaction = storage.start_transaction().await?;
+    let mut outputs = Vec::new();
+    for data in [&token_uri_data, &owner_of_data] {
+        let resp_data = calls_helper
+            .execute(
+                &mut transaction,
+                nft_factory_address,
+                data.clone(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        outputs.push(resp_data);
+    }
+
+    // Postgres rejects strings with the NUL character, after such a failure
+    // every query fails until the end of the transaction.
+    let invalid_token = zksync_types::Token::new(
+        TokenId(u16::MAX as u32),
+        H160::random(),
+        "\0",
+        18,
+        zksync_types::TokenKind::ERC20,
+    );
+    assert!(transaction
+        .tokens_schema()
+        .store_token(invalid_token)
+        .await
+        .is_err());
+    let resp_data = calls_helper
+        .execute(
+            &mut transaction,
+            nft_factory_address,
+            token_uri_data.clone(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp_data, outputs[0]);
+    // The NFT may be not minted at the historical block, so it isn't answered from the cache.
+    assert!(calls_helper
+        .execute(
+            &mut transaction,
+            nft_factory_address,
+            token_uri_data,
+            Some(BlockNumber(1)),
+            None
+        )
+        .await
+        .is_err());
+    // The owner may change, so it's always loaded from the storage.
+    assert!(calls_helper
+        .execute(
+            &mut transaction,
+            nft_factory_address,
+            owner_of_data,
+            None,
+            None
+        )
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+/// Checks that calls with empty data are answered without output for both proxies and tokens.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn empty_calldata() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+
+    for to in [
+        H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap(),
+        H160::from_str(NFT_FACTORY_ADDRESS).unwrap(),
+        token.address,
+    ] {
+        let outcome = calls_helper
+            .execute_outcome(&mut storage, to, Vec::new(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, ExecuteOutcome::Answered(Vec::new()), "{:?}", to);
+    }
+    // Addresses that aren't emulated return nothing as well, but they aren't answered.
+    let resp_data = calls_helper
+        .execute(&mut storage, H160::random(), Vec::new(), None, None)
+        .await
+        .unwrap();
+    assert!(resp_data.is_empty());
+
+    Ok(())
+}
+
+/// Checks that `getCreator` returns the same values as `creatorId` and `creatorAddress`.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_get_creator() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let token_id = ethabi::encode(&[Token::Uint(U256::from(65544))]);
+    let call_data = |selector: [u8; 4]| [selector.to_vec(), token_id.clone()].concat();
+
+    // `getCreator(uint256)`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            call_data([0xd4, 0x8e, 0x63, 0x8a]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let creator = ethabi::decode(&[ParamType::Uint(32), ParamType::Address], &resp_data).unwrap();
+    // `creatorId(uint256)`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            call_data([0x8d, 0x6a, 0x62, 0xb2]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let creator_id = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+    // `creatorAddress(uint256)`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            call_data([0xb2, 0xa9, 0x99, 0xc7]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let creator_address = ethabi::decode(&[ParamType::Address], &resp_data).unwrap();
+    assert_eq!(creator, [creator_id[0].clone(), creator_address[0].clone()]);
+
+    // Nonexistent NFTs revert as with the individual getters.
+    let mut data = vec![0xd4, 0x8e, 0x63, 0x8a];
+    data.extend(ethabi::encode(&[Token::Uint(U256::from(
+        NFT_TOKEN_ID_VAL - 1,
+    ))]));
+    let error = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.message,
+        "execution reverted: creator query for nonexistent token"
+    );
+
+    Ok(())
+}
+
+/// Checks that `execute_outcome` classifies the calls and `execute` keeps returning the same data.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn execute_outcome() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let other_token = storage
+        .tokens_schema()
+        .get_token(TokenId(16).into())
+        .await?
+        .unwrap();
+    // Only the first token is allowed, the second one isn't a contract then.
+    let config = Web3Config {
+        call_allowed_tokens: Some(vec![token.address]),
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    // `decimals()`
+    let decimals_data = vec![0x31, 0x3c, 0xe5, 0x67];
+
+    let cases = vec![
+        (
+            token.address,
+            decimals_data.clone(),
+            ExecuteOutcome::Answered(ethabi::encode(&[Token::Uint(U256::from(token.decimals))])),
+        ),
+        (
+            nft_factory_address,
+            Vec::new(),
+            ExecuteOutcome::Answered(Vec::new()),
+        ),
+        (
+            H160::zero(),
+            decimals_data.clone(),
+            ExecuteOutcome::NotAContract,
+        ),
+        (
+            other_token.address,
+            decimals_data.clone(),
+            ExecuteOutcome::NotAContract,
+        ),
+        (
+            nft_factory_address,
+            vec![0xff, 0xff, 0xff, 0xff],
+            ExecuteOutcome::UnknownSelector([0xff, 0xff, 0xff, 0xff]),
+        ),
+        // Empty data is classified the same way as any other call.
+        (
+            token.address,
+            Vec::new(),
+            ExecuteOutcome::Answered(Vec::new()),
+        ),
+        (H160::zero(), Vec::new(), ExecuteOutcome::NotAContract),
+        (
+            other_token.address,
+            Vec::new(),
+            ExecuteOutcome::NotAContract,
+        ),
+        (
+            H160::random(),
+            decimals_data.clone(),
+            ExecuteOutcome::NotAContract,
+        ),
+    ];
+    for (to, data, expected_outcome) in cases {
+        let outcome = calls_helper
+            .execute_outcome(&mut storage, to, data.clone(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, expected_outcome, "{:?}", to);
+        let result = calls_helper
+            .execute(&mut storage, to, data, None, None)
+            .await;
+        assert_eq!(result, expected_outcome.into_result());
+    }
+
+    // Without the allow-list the unknown addresses are missing tokens.
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    for data in [decimals_data, Vec::new()] {
+        let outcome = calls_helper
+            .execute_outcome(&mut storage, H160::random(), data, None, None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, ExecuteOutcome::MissingToken);
+    }
+
+    // Data too short to contain a selector is still an error.
+    assert!(calls_helper
+        .execute_outcome(&mut storage, nft_factory_address, vec![0xff], None, None)
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+/// Checks that `classify` routes the calls the same way as `execute` does.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn classify() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+
+    let route = calls_helper
+        .classify(&mut storage, H160::from_str(NFT_FACTORY_ADDRESS).unwrap())
+        .await
+        .unwrap();
+    assert!(matches!(route, Route::Proxy));
+    let route = calls_helper
+        .classify(&mut storage, token.address)
+        .await
+        .unwrap();
+    assert!(matches!(route, Route::Erc20(_)));
+    let route = calls_helper
+        .classify(&mut storage, nft.address)
+        .await
+        .unwrap();
+    assert!(matches!(route, Route::NftEmpty));
+    for address in [H160::random(), H160::zero()] {
+        let route = calls_helper.classify(&mut storage, address).await.unwrap();
+        assert!(matches!(route, Route::Unknown));
+    }
+
+    // The token isn't allowed, so it isn't routed to the ERC20 emulation.
+    let config = Web3Config {
+        call_allowed_tokens: Some(Vec::new()),
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    let route = calls_helper
+        .classify(&mut storage, token.address)
+        .await
+        .unwrap();
+    assert!(matches!(route, Route::Unknown));
+
+    Ok(())
+}
+
+/// Checks that `execute_hex` decodes the hex calldata and rejects the malformed one.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn execute_hex() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let to = format!("{:#x}", token.address);
+
+    // `decimals()`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            token.address,
+            vec![0x31, 0x3c, 0xe5, 0x67],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let output = calls_helper
+        .execute_hex(&mut storage, &to, "0x313ce567")
+        .await
+        .unwrap();
+    assert_eq!(output, format!("0x{}", hex::encode(resp_data)));
+
+    let cases = vec![
+        (to.as_str(), "0x313ce56", "data: Odd number of digits"),
+        (
+            to.as_str(),
+            "0x313ce5zz",
+            "data: Invalid character 'z' at position 6",
+        ),
+        (to.as_str(), "313ce567", "data: expected 0x-prefixed hex"),
+        ("0x1234", "0x313ce567", "to: expected 20 bytes, got 2"),
+        ("0xabc", "0x313ce567", "to: Odd number of digits"),
+    ];
+    for (to, data, message) in cases {
+        let error = calls_helper
+            .execute_hex(&mut storage, to, data)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidParams);
+        assert_eq!(error.message, message);
+    }
+
+    Ok(())
+}
+
+/// Checks that `getNftSummary` returns the fields of the minted NFT.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_get_summary() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+
+    // `getNftSummary(uint256)`
+    let mut data = vec![0xf9, 0x4c, 0xbe, 0x24];
+    data.extend(ethabi::encode(&[Token::Uint(U256::from(nft.id.0))]));
+    let resp_data = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap();
+    let summary = ethabi::decode(
+        &[
+            ParamType::Uint(32),
+            ParamType::Address,
+            ParamType::Uint(32),
+            ParamType::FixedBytes(32),
+        ],
+        &resp_data,
+    )
+    .unwrap();
+    assert_eq!(
+        summary,
+        vec![
+            Token::Uint(U256::from(nft.creator_id.0)),
+            Token::Address(nft.creator_address),
+            Token::Uint(U256::from(nft.serial_id)),
+            Token::FixedBytes(nft.content_hash.as_bytes().to_vec()),
+        ]
+    );
+
+    // Nonexistent NFTs revert as with the individual getters.
+    let mut data = vec![0xf9, 0x4c, 0xbe, 0x24];
+    data.extend(ethabi::encode(&[Token::Uint(U256::from(
+        NFT_TOKEN_ID_VAL - 1,
+    ))]));
+    let error = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.message,
+        "execution reverted: summary query for nonexistent token"
+    );
+
+    Ok(())
+}
+
+/// Checks that `balancesOf` returns the same balances as `balanceOf` of each token.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_balances_of() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let owner = storage
+        .chain()
+        .account_schema()
+        .account_address_by_id(AccountId(3))
+        .await?
+        .unwrap();
+    let mut tokens = Vec::new();
+    for token_id in [1, 16] {
+        let token = storage
+            .tokens_schema()
+            .get_token(TokenId(token_id).into())
+            .await?
+            .unwrap();
+        tokens.push(token.address);
+    }
+
+    let mut expected_balances = Vec::new();
+    for token in &tokens {
+        // `balanceOf(address)`
+        let mut data = vec![0x70, 0xa0, 0x82, 0x31];
+        data.extend(ethabi::encode(&[Token::Address(owner)]));
+        let resp_data = calls_helper
+            .execute(&mut storage, *token, data, None, None)
+            .await
+            .unwrap();
+        expected_balances.extend(ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap());
+    }
+    // The unknown token has no balance.
+    tokens.push(H160::random());
+    expected_balances.push(Token::Uint(U256::zero()));
+
+    // `balancesOf(address,address[])`
+    let balances_of = |tokens: &[H160]| {
+        let mut data = vec![0xe0, 0x98, 0xe8, 0x44];
+        data.extend(ethabi::encode(&[
+            Token::Address(owner),
+            Token::Array(tokens.iter().copied().map(Token::Address).collect()),
+        ]));
+        data
+    };
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            balances_of(&tokens),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let balances = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Uint(256)))],
+        &resp_data,
+    )
+    .unwrap();
+    assert_eq!(balances, vec![Token::Array(expected_balances.clone())]);
+
+    // Only the first token is allowed, the balance of the second one isn't revealed.
+    let config = Web3Config {
+        call_allowed_tokens: Some(vec![tokens[0]]),
+        ..cfg.config.api.web3.clone()
+    };
+    let restricted_calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    let resp_data = restricted_calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            balances_of(&tokens[0..2]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let balances = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Uint(256)))],
+        &resp_data,
+    )
+    .unwrap();
+    assert_eq!(
+        balances,
+        vec![Token::Array(vec![
+            expected_balances[0].clone(),
+            Token::Uint(U256::zero())
+        ])]
+    );
+
+    let error = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            balances_of(&[tokens[0]; 101]),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.message,
+        "execution reverted: balancesOf: tokens exceed 100"
+    );
+
+    Ok(())
+}
+
+/// Checks `totalSupply` of the NFT collection in both modes.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_total_supply_modes() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let block = storage
+        .chain()
+        .block_schema()
+        .get_last_verified_confirmed_block()
+        .await?;
+    let minted_count = storage.tokens_schema().get_minted_nfts_count(block).await?;
+
+    for (total_supply, max_token_id, expected) in [
+        (NftTotalSupply::LiveCount, None, minted_count),
+        (
+            NftTotalSupply::FixedMax,
+            None,
+            NFT_TOKEN_ID_VAL - MIN_NFT_TOKEN_ID,
+        ),
+        (NftTotalSupply::FixedMax, Some(MIN_NFT_TOKEN_ID + 9), 10),
+    ] {
+        let config = Web3Config {
+            nft_total_supply: total_supply,
+            nft_max_token_id: max_token_id,
+            ..cfg.config.api.web3.clone()
+        };
+        let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+        // `totalSupply()`
+        let resp_data = calls_helper
+            .execute(
+                &mut storage,
+                nft_factory_address,
+                vec![0x18, 0x16, 0x0d, 0xdd],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap(),
+            vec![Token::Uint(U256::from(expected))],
+            "{:?}",
+            total_supply
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that `resolve_block_tag` resolves `latest` and `pending` to the last saved
+/// or the pending block, `earliest` to the first block and other tags as the other methods do.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn resolve_block_tag() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let mut block_schema = storage.chain().block_schema();
+    let last_verified = block_schema.get_last_verified_confirmed_block().await?;
+    let last_committed = block_schema.get_last_committed_confirmed_block().await?;
+    let last_saved = block_schema.get_last_saved_block().await?;
+    assert!(block_schema.load_pending_block().await?.is_none());
+
+    for (tag, expected) in [
+        (BlockTag::Latest, last_saved),
+        (BlockTag::Pending, last_saved),
+        (BlockTag::Finalized, last_verified),
+        (BlockTag::Committed, last_committed),
+        (BlockTag::Earliest, BlockNumber(1)),
+        (BlockTag::Number(U64::from(1)), BlockNumber(1)),
+    ] {
+        let block = calls_helper
+            .resolve_block_tag(&mut storage, tag)
+            .await
+            .unwrap();
+        assert_eq!(block, expected, "{:?}", tag);
+    }
+
+    let error = calls_helper
+        .resolve_block_tag(&mut storage, BlockTag::Number(U64::from(*last_saved + 1)))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidParams);
+
+    let pending_block = PendingBlock {
+        number: last_saved + 1,
+        chunks_left: 10,
+        unprocessed_priority_op_before: 0,
+        pending_block_iteration: 1,
+        success_operations: Vec::new(),
+        failed_txs: Vec::new(),
+        timestamp: 0,
+    };
+    let mut transaction = storage.start_transaction().await?;
+    transaction
+        .chain()
+        .block_schema()
+        .save_pending_block(pending_block)
+        .await?;
+    let block = calls_helper
+        .resolve_block_tag(&mut transaction, BlockTag::Pending)
+        .await
+        .unwrap();
+    assert_eq!(block, last_saved + 1);
+    let block = calls_helper
+        .resolve_block_tag(&mut transaction, BlockTag::Latest)
+        .await
+        .unwrap();
+    assert_eq!(block, last_saved);
+
+    Ok(())
+}
+
+/// Checks that `get_storage_at` returns the ERC20 balance at the canonical balance slot.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_storage_at() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let mut token_holder = None;
+    for token in storage
+        .tokens_schema()
+        .load_tokens_asc(TokenId(1), None)
+        .await?
+    {
+        let holders = storage
+            .chain()
+            .account_schema()
+            .get_token_holders(token.id, 1)
+            .await?;
+        if let (false, Some(holder)) = (token.is_nft, holders.first()) {
+            token_holder = Some((token, *holder));
+            break;
+        }
+    }
+    let (token, holder) = token_holder.expect("no holders of ERC20 tokens in the test data");
+    // `keccak256(abi.encode(holder, 0))`
+    let mut preimage = [0u8; 64];
+    preimage[12..32].copy_from_slice(holder.as_bytes());
+    let slot = H256(tiny_keccak::keccak256(&preimage));
+    assert_eq!(CallsHelper::erc20_balance_slot(holder), slot);
+
+    // `balanceOf(address)`
+    let mut data = vec![0x70, 0xa0, 0x82, 0x31];
+    data.extend(ethabi::encode(&[Token::Address(holder)]));
+    let balance = calls_helper
+        .execute(&mut storage, token.address, data, None, None)
+        .await
+        .unwrap();
+    let word = calls_helper
+        .get_storage_at(&mut storage, token.address, slot, None)
+        .await
+        .unwrap();
+    assert!(!word.is_zero());
+    assert_eq!(word.as_bytes(), balance.as_slice());
+
+    // Unrecognized slots and non-token addresses have the zero word.
+    let word = calls_helper
+        .get_storage_at(&mut storage, token.address, H256::random(), None)
+        .await
+        .unwrap();
+    assert!(word.is_zero());
+    let word = calls_helper
+        .get_storage_at(&mut storage, H160::random(), slot, None)
+        .await
+        .unwrap();
+    assert!(word.is_zero());
+
+    Ok(())
+}
+
+/// Checks that ERC20 `symbol` and `name` get the configured affixes, unless overridden.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_symbol_affixes() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let overridden_token = storage
+        .tokens_schema()
+        .get_token(TokenId(2).into())
+        .await?
+        .unwrap();
+    let config = Web3Config {
+        erc20_symbol_prefix: Some("zk".to_string()),
+        erc20_symbol_suffix: Some(".e".to_string()),
+        erc20_symbol_overrides: Some(vec![format!("{:#x}:USDC.e", overridden_token.address)]),
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+
+    let mut results = Vec::new();
+    for address in [token.address, overridden_token.address] {
+        // `name()` and `symbol()`
+        let mut outputs = Vec::new();
+        for data in [vec![0x06, 0xfd, 0xde, 0x03], vec![0x95, 0xd8, 0x9b, 0x41]] {
+            let resp_data = calls_helper
+                .execute(&mut storage, address, data, None, None)
+                .await
+                .unwrap();
+            let output = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+            outputs.push(output[0].clone().into_string().unwrap());
+        }
+        results.push(outputs);
+    }
+    let name = storage
+        .tokens_schema()
+        .get_token_name(token.id)
+        .await?
+        .unwrap_or_else(|| token.symbol.clone());
+    let overridden_name = storage
+        .tokens_schema()
+        .get_token_name(overridden_token.id)
+        .await?
+        .unwrap_or_else(|| overridden_token.symbol.clone());
+    assert_eq!(
+        results,
+        vec![
+            vec![format!("zk{}.e", name), format!("zk{}.e", token.symbol)],
+            vec![overridden_name, "USDC.e".to_string()],
+        ]
+    );
+
+    let config = Web3Config {
+        erc20_symbol_overrides: Some(vec!["USDC.e".to_string()]),
+        ..cfg.config.api.web3.clone()
+    };
+    assert!(CallsHelper::new(&config, Duration::from_secs(1)).is_err());
+
+    Ok(())
+}
+
+/// Checks that `self_test` passes with the stock ABIs and reports the functions
+/// the emulation doesn't match, including the unexpected reverts.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn self_test() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let config = Web3Config {
+        call_strict_abi: false,
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    calls_helper.self_test(&mut storage).await.unwrap();
+    // The first ERC20 token is the fixture.
+    let token = storage
+        .tokens_schema()
+        .load_tokens_asc(TokenId(1), Some(1))
+        .await?
+        .remove(0);
+
+    let erc20_abi: Vec<Value> = serde_json::from_str(ERC20_ABI).unwrap();
+    // `mint` has no emulation, `decimals` is declared to return two words instead of one.
+    let mint = serde_json::json!({
+        "inputs": [{"internalType": "uint256", "name": "amount", "type": "uint256"}],
+        "name": "mint",
+        "outputs": [],
+        "stateMutability": "nonpayable",
+        "type": "function"
+    });
+    let mut decimals_abi = erc20_abi.clone();
+    for function in &mut decimals_abi {
+        if function["name"] == "decimals" {
+            function["outputs"] = serde_json::json!([
+                {"internalType": "uint8", "name": "", "type": "uint8"},
+                {"internalType": "uint8", "name": "", "type": "uint8"}
+            ]);
+        }
+    }
+    // The fixture of `index` is zero, the one of `tokenId` is the NFT ID, which is out of bounds
+    // as an index, and the view functions must not revert.
+    let nft_factory_abi: Vec<Value> = serde_json::from_str(NFT_FACTORY_ABI).unwrap();
+    let mut token_by_id_abi = nft_factory_abi.clone();
+    for function in &mut token_by_id_abi {
+        if function["name"] == "tokenByIndex" {
+            function["inputs"][0]["name"] = Value::from("tokenId");
+        }
+    }
+    let cases = vec![
+        (
+            [erc20_abi.clone(), vec![mint]].concat(),
+            nft_factory_abi.clone(),
+            "self-test of ERC20.mint failed: function mint is not implemented".to_string(),
+        ),
+        (
+            decimals_abi,
+            nft_factory_abi,
+            format!(
+                "self-test of ERC20.decimals failed: output 0x{} doesn't match the ABI",
+                hex::encode(ethabi::encode(&[Token::Uint(token.decimals.into())]))
+            ),
+        ),
+        (
+            erc20_abi,
+            token_by_id_abi,
+            "self-test of NFTFactory.tokenByIndex failed: \
+             execution reverted: ERC721Enumerable: global index out of bounds"
+                .to_string(),
+        ),
+    ];
+    for (erc20_abi, nft_factory_abi, message) in cases {
+        let dir = std::env::temp_dir().join(format!("web3-abi-{:x}", H160::random()));
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(
+            dir.join("ERC20.json"),
+            serde_json::to_string(&erc20_abi).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("NFTFactory.json"),
+            serde_json::to_string(&nft_factory_abi).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.join("ERC1155.json"), ERC1155_ABI).unwrap();
+        std::fs::write(dir.join("Multicall3.json"), MULTICALL3_ABI).unwrap();
+        let calls_helper = CallsHelper::with_abi_dir(&config, Duration::from_secs(1), Some(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let error = calls_helper?.self_test(&mut storage).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::InternalError);
+        assert_eq!(error.message, message);
+    }
+
+    Ok(())
+}
+
+/// Checks that malformed calls to the emulated contracts are reported as errors.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn malformed_calldata() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    for to in [token.address, nft_factory_address] {
+        for (data, message) in [
+            (
+                vec![0x70, 0xa0, 0x82],
+                "calldata too short to contain a selector",
+            ),
+            (
+                vec![0xff, 0xff, 0xff, 0xff],
+                "unknown function selector 0xffffffff",
+            ),
+        ] {
+            let error = calls_helper
+                .execute(&mut storage, to, data, None, None)
+                .await
+                .unwrap_err();
+            assert_eq!(error.code, ErrorCode::InvalidParams);
+            assert_eq!(error.message, message);
+        }
+    }
+
+    // Addresses of the contracts that aren't emulated still return nothing.
+    let resp_data = calls_helper
+        .execute(&mut storage, H160::random(), vec![], None, None)
+        .await
+        .unwrap();
+    assert!(resp_data.is_empty());
+
+    Ok(())
+}
+
+/// Checks the mapping between NFT token IDs and their L2 addresses.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_token_address_lookup() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    // `getTokenAddress(uint256)`
+    let mut data = vec![0x67, 0xcc, 0xdf, 0x38];
+    data.extend(ethabi::encode(&[Token::Uint(U256::from(nft.id.0))]));
+    let resp_data = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Address], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_address().unwrap(), nft.address);
+
+    // `getTokenId(address)`
+    let mut data = vec![0xf1, 0x53, 0x76, 0x86];
+    data.extend(ethabi::encode(&[Token::Address(nft.address)]));
+    let resp_data = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_uint().unwrap(),
+        U256::from(nft.id.0)
+    );
+
+    // Unknown tokens, including IDs that don't fit into `u32`, revert.
+    for (data, reason) in [
+        (
+            [
+                vec![0x67, 0xcc, 0xdf, 0x38],
+                ethabi::encode(&[Token::Uint(U256::from(u32::MAX) + 1)]),
+            ]
+            .concat(),
+            "token address query for nonexistent token",
+        ),
+        (
+            [
+                vec![0xf1, 0x53, 0x76, 0x86],
+                ethabi::encode(&[Token::Address(H160::random())]),
+            ]
+            .concat(),
+            "token ID query for nonexistent token",
+        ),
+    ] {
+        let error = calls_helper
+            .execute(&mut storage, nft_factory_address, data, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(3));
+        assert_eq!(error.message, format!("execution reverted: {}", reason));
+    }
+
+    Ok(())
+}
+
+/// Checks that calls to the zero address are answered without accessing the storage.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn zero_address_call() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+
+    // Postgres rejects strings with the NUL character, after such a failure
+    // every query fails until the end of the transaction.
+    let mut transaction = storage.start_transaction().await?;
+    let invalid_token = zksync_types::Token::new(
+        TokenId(u16::MAX as u32),
+        H160::random(),
+        "\0",
+        18,
+        zksync_types::TokenKind::ERC20,
+    );
+    assert!(transaction
+        .tokens_schema()
+        .store_token(invalid_token)
+        .await
+        .is_err());
+
+    // `balanceOf(address)`
+    let mut data = vec![0x70, 0xa0, 0x82, 0x31];
+    data.extend(ethabi::encode(&[Token::Address(H160::random())]));
+    let resp_data = calls_helper
+        .execute(&mut transaction, H160::zero(), data.clone(), None, None)
+        .await
+        .unwrap();
+    assert!(resp_data.is_empty());
+
+    // The same call to the token contract hits the storage and fails.
+    assert!(calls_helper
+        .execute(&mut transaction, token.address, data, None, None)
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+/// Checks that NFT metadata calls reuse the NFT fetched by the first call.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_cache() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let nft = NFT::new(
+        TokenId(81234),
+        1,
+        AccountId(1),
+        H160::random(),
+        H160::random(),
+        None,
+        H256::random(),
+    );
+    let token_id = ethabi::encode(&[Token::Uint(U256::from(nft.id.0))]);
+    let call_data = |selector: [u8; 4]| [selector.to_vec(), token_id.clone()].concat();
+
+    // The NFT exists only in the rolled back transaction, so the following calls
+    // can find it only in the cache.
+    {
+        let mut transaction = storage.start_transaction().await?;
+        let update = (
+            nft.creator_id,
+            AccountUpdate::MintNFT {
+                token: nft.clone(),
+                nonce: Nonce(0),
+            },
+        );
+        transaction
+            .chain()
+            .state_schema()
+            .commit_state_update(BlockNumber(1), &[update], 0)
+            .await?;
+        // `creatorId(uint256)`
+        let resp_data = calls_helper
+            .execute(
+                &mut transaction,
+                nft_factory_address,
+                call_data([0x8d, 0x6a, 0x62, 0xb2]),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+        assert_eq!(
+            outputs[0].clone().into_uint().unwrap(),
+            U256::from(nft.creator_id.0)
+        );
+    }
+
+    // `creatorAddress(uint256)`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            call_data([0xb2, 0xa9, 0x99, 0xc7]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Address], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_address().unwrap(),
+        nft.creator_address
+    );
+
+    // `serialId(uint256)`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            call_data([0xe2, 0xd3, 0x28, 0xdf]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_uint().unwrap(),
+        U256::from(nft.serial_id)
+    );
+
+    // `contentHash(uint256)`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            call_data([0xf3, 0xe0, 0xc2, 0x90]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::FixedBytes(32)], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_fixed_bytes().unwrap(),
+        nft.content_hash.as_bytes().to_vec()
+    );
+
+    // `tokenURI(uint256)`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            call_data([0xc8, 0x7b, 0x56, 0xdd]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_string().unwrap(),
+        calls_helper.nft_token_uri(&nft).unwrap()
+    );
+
+    // `ownerOf(uint256)` finds the NFT in the cache, but the owner is loaded from the storage,
+    // where the NFT has no owner.
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            call_data([0x63, 0x52, 0x21, 0x1e]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Address], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_address().unwrap(), H160::zero());
+
+    Ok(())
+}
+
+/// Checks ERC1155 balances of NFTs served by the NFT factory.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc1155_calls() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let block = storage
+        .chain()
+        .block_schema()
+        .get_last_committed_block()
+        .await?;
+    let token_id = U256::from(nft.id.0);
+
+    // `ownerOf(uint256)`
+    let mut data = vec![0x63, 0x52, 0x21, 0x1e];
+    data.extend(ethabi::encode(&[Token::Uint(token_id)]));
+    let resp_data = calls_helper
+        .execute(&mut storage, nft_factory_address, data, Some(block), None)
+        .await
+        .unwrap();
+    let owner = ethabi::decode(&[ParamType::Address], &resp_data).unwrap()[0]
+        .clone()
+        .into_address()
+        .unwrap();
+    let other = H160::random();
+
+    // `balanceOf(address,uint256)`
+    for (address, expected_balance) in [(owner, 1u32), (other, 0)] {
+        let mut data = vec![0x00, 0xfd, 0xd5, 0x8e];
+        data.extend(ethabi::encode(&[
+            Token::Address(address),
+            Token::Uint(token_id),
+        ]));
+        let resp_data = calls_helper
+            .execute(&mut storage, nft_factory_address, data, Some(block), None)
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+        assert_eq!(
+            outputs[0].clone().into_uint().unwrap(),
+            U256::from(expected_balance)
+        );
+    }
+
+    // `balanceOfBatch(address[],uint256[])`
+    let balance_of_batch = |addresses: Vec<H160>, token_ids: Vec<U256>| {
+        let mut data = vec![0x4e, 0x12, 0x73, 0xf4];
+        data.extend(ethabi::encode(&[
+            Token::Array(addresses.into_iter().map(Token::Address).collect()),
+            Token::Array(token_ids.into_iter().map(Token::Uint).collect()),
+        ]));
+        data
+    };
+    let data = balance_of_batch(
+        vec![owner, other, owner],
+        vec![token_id, token_id, U256::from(u32::MAX) + 1],
+    );
+    let resp_data = calls_helper
+        .execute(&mut storage, nft_factory_address, data, Some(block), None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Uint(256)))],
+        &resp_data,
+    )
+    .unwrap();
+    assert_eq!(
+        outputs[0].clone().into_array().unwrap(),
+        vec![
+            Token::Uint(U256::one()),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero())
+        ]
+    );
+
+    // Fungible IDs aren't ERC1155 tokens, the owner has no balance of them,
+    // NFTs aren't reported either if they are out of the allow-list.
+    let data = balance_of_batch(vec![owner, owner], vec![U256::zero(), U256::one()]);
+    let resp_data = calls_helper
+        .execute(&mut storage, nft_factory_address, data, Some(block), None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Uint(256)))],
+        &resp_data,
+    )
+    .unwrap();
+    assert_eq!(
+        outputs[0].clone().into_array().unwrap(),
+        vec![Token::Uint(U256::zero()), Token::Uint(U256::zero())]
+    );
+    let config = Web3Config {
+        call_allowed_tokens: Some(Vec::new()),
+        ..cfg.config.api.web3.clone()
+    };
+    let restricted_calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    let data = balance_of_batch(vec![owner], vec![token_id]);
+    let resp_data = restricted_calls_helper
+        .execute(&mut storage, nft_factory_address, data, Some(block), None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Uint(256)))],
+        &resp_data,
+    )
+    .unwrap();
+    assert_eq!(
+        outputs[0].clone().into_array().unwrap(),
+        vec![Token::Uint(U256::zero())]
+    );
+
+    let data = balance_of_batch(vec![owner, other], vec![token_id]);
+    let error = calls_helper
+        .execute(&mut storage, nft_factory_address, data, Some(block), None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::ServerError(3));
+    assert_eq!(
+        error.message,
+        "execution reverted: ERC1155: accounts and ids length mismatch"
+    );
+
+    Ok(())
+}
+
+/// Checks that ERC20 `allowance` follows the configured policy.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_allowance_policy() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let zksync_proxy_address = H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap();
+    let other_address = H160::random();
+
+    for (policy, proxy_allowance, other_allowance) in [
+        (
+            AllowancePolicy::AlwaysMax,
+            U256::max_value(),
+            U256::max_value(),
+        ),
+        (AllowancePolicy::AlwaysZero, U256::zero(), U256::zero()),
+        (
+            AllowancePolicy::ProxyOnlyMax,
+            U256::max_value(),
+            U256::zero(),
+        ),
+    ] {
+        let config = Web3Config {
+            erc20_allowance_policy: policy,
+            zksync_proxy_addresses: vec![zksync_proxy_address],
+            ..cfg.config.api.web3.clone()
+        };
+        let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+        for (spender, expected_allowance) in [
+            (zksync_proxy_address, proxy_allowance),
+            (other_address, other_allowance),
+        ] {
+            // `allowance(address,address)`
+            let mut data = vec![0xdd, 0x62, 0xed, 0x3e];
+            data.extend(ethabi::encode(&[
+                Token::Address(H160::random()),
+                Token::Address(spender),
+            ]));
+            let resp_data = calls_helper
+                .execute(&mut storage, token.address, data, None, None)
+                .await
+                .unwrap();
+            let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+            assert_eq!(
+                outputs[0].clone().into_uint().unwrap(),
+                expected_allowance,
+                "{:?}",
+                policy
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that enumeration calls fail instead of scanning too many storage rows.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn call_max_scanned_rows() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let owner = H160::random();
+    // `tokenOfOwnerByIndex(address,uint256)`
+    let token_of_owner_by_index = |index: U256| {
+        let mut data = vec![0x2f, 0x74, 0x5c, 0x59];
+        data.extend(ethabi::encode(&[Token::Address(owner), Token::Uint(index)]));
+        data
+    };
+
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    for index in [U256::from(u32::MAX), U256::max_value()] {
+        let error = calls_helper
+            .execute(
+                &mut storage,
+                nft_factory_address,
+                token_of_owner_by_index(index),
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(-32000));
+        assert_eq!(error.message, "out of gas");
+    }
+
+    let config = Web3Config {
+        call_max_scanned_rows: 10,
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    let error = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            token_of_owner_by_index(U256::from(10)),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.message, "out of gas");
+    // Indices within the budget are looked up as usual.
+    let error = calls_helper
+        .execute(
+            &mut storage,
+            nft_factory_address,
+            token_of_owner_by_index(U256::from(9)),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::ServerError(3));
+    assert_eq!(
+        error.message,
+        "execution reverted: ERC721Enumerable: owner index out of bounds"
+    );
+
+    Ok(())
+}
+
+/// Checks that `decimals()` is encoded as a single zero-padded word, so it is decoded
+/// both as `uint8` and as `uint256`.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_decimals_encoding() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+
+    // The selector doesn't depend on the return type of `decimals()`.
+    let selector = tiny_keccak::keccak256(b"decimals()")[0..4].to_vec();
+    assert_eq!(selector, vec![0x31, 0x3c, 0xe5, 0x67]);
+    assert!(calls_helper.has_function(token.address, [0x31, 0x3c, 0xe5, 0x67]));
+
+    let resp_data = calls_helper
+        .execute(&mut storage, token.address, selector, None, None)
+        .await
+        .unwrap();
+    let mut expected = vec![0u8; 32];
+    expected[31] = token.decimals;
+    assert_eq!(resp_data, expected);
+
+    Ok(())
+}
+
+/// Checks the simulation of ERC20 and NFT transfers.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn simulate_transfer() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let address = storage
+        .chain()
+        .account_schema()
+        .account_address_by_id(AccountId(3))
+        .await?
+        .unwrap();
+    let last_verified_block = storage
+        .chain()
+        .block_schema()
+        .get_last_verified_confirmed_block()
+        .await?;
+    let balance = storage
+        .chain()
+        .account_schema()
+        .get_account_balance_for_block(address, last_verified_block, token.id)
+        .await?;
+    let balance = u256_from_biguint(balance);
+    for (amount, expected) in [
+        (balance, TransferSimulation::Ok),
+        (balance + 1, TransferSimulation::InsufficientBalance),
+    ] {
+        let simulation = calls_helper
+            .simulate_transfer(&mut storage, token.address, address, amount)
+            .await
+            .unwrap();
+        assert_eq!(simulation, expected);
+    }
+
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    let owner_id = storage
+        .chain()
+        .account_schema()
+        .get_nft_owner(nft.id)
+        .await?
+        .unwrap();
+    let owner = storage
+        .chain()
+        .account_schema()
+        .account_address_by_id(owner_id)
+        .await?
+        .unwrap();
+    for (from, amount, expected) in [
+        (owner, U256::one(), TransferSimulation::Ok),
+        (
+            owner,
+            U256::from(2),
+            TransferSimulation::InsufficientBalance,
+        ),
+        (
+            H160::random(),
+            U256::one(),
+            TransferSimulation::InsufficientBalance,
+        ),
+    ] {
+        let simulation = calls_helper
+            .simulate_transfer(&mut storage, nft.address, from, amount)
+            .await
+            .unwrap();
+        assert_eq!(simulation, expected);
+    }
+
+    let simulation = calls_helper
+        .simulate_transfer(&mut storage, H160::random(), address, U256::one())
+        .await
+        .unwrap();
+    assert_eq!(simulation, TransferSimulation::UnknownToken);
+
+    Ok(())
+}
+
+/// Checks `getApproved` with and without the legacy proxy approval.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_get_approved() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let zksync_proxy_address = H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap();
+
+    for (get_approved_proxy, expected_approved) in
+        [(false, H160::zero()), (true, zksync_proxy_address)]
+    {
+        let config = Web3Config {
+            nft_get_approved_proxy: get_approved_proxy,
+            zksync_proxy_addresses: vec![zksync_proxy_address],
+            ..cfg.config.api.web3.clone()
+        };
+        let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+        // `getApproved(uint256)`
+        let mut data = vec![0x08, 0x18, 0x12, 0xfc];
+        data.extend(ethabi::encode(&[Token::Uint(U256::from(nft.id.0))]));
+        let resp_data = calls_helper
+            .execute(&mut storage, nft_factory_address, data, None, None)
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::Address], &resp_data).unwrap();
+        assert_eq!(
+            outputs[0].clone().into_address().unwrap(),
+            expected_approved
+        );
+        // The proxy stays an approved operator in both modes.
+        // `isApprovedForAll(address,address)`
+        let mut data = vec![0xe9, 0x85, 0xe9, 0xc5];
+        data.extend(ethabi::encode(&[
+            Token::Address(H160::random()),
+            Token::Address(zksync_proxy_address),
+        ]));
+        let resp_data = calls_helper
+            .execute(&mut storage, nft_factory_address, data, None, None)
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::Bool], &resp_data).unwrap();
+        assert!(outputs[0].clone().into_bool().unwrap());
+    }
+
+    Ok(())
+}
+
+/// Checks that `getApproved` reports the stored approval and falls back to the zero address.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_stored_approval() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    // `getApproved(uint256)`
+    let mut data = vec![0x08, 0x18, 0x12, 0xfc];
+    data.extend(ethabi::encode(&[Token::Uint(U256::from(65544))]));
+
+    // The approval is stored only in the rolled back transaction.
+    {
+        let mut transaction = storage.start_transaction().await?;
+        let operator = H160::random();
+        transaction
+            .tokens_schema()
+            .store_nft_approval(TokenId(65544), operator)
+            .await?;
+        let resp_data = calls_helper
+            .execute(
+                &mut transaction,
+                nft_factory_address,
+                data.clone(),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::Address], &resp_data).unwrap();
+        assert_eq!(outputs[0].clone().into_address().unwrap(), operator);
+    }
+
+    let resp_data = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Address], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_address().unwrap(), H160::zero());
+
+    Ok(())
+}
+
+/// Checks that `approve` and `setApprovalForAll` revert since approvals can't be changed.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_approve_reverts() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    // `approve(address,uint256)`
+    let mut approve = vec![0x09, 0x5e, 0xa7, 0xb3];
+    approve.extend(ethabi::encode(&[
+        Token::Address(H160::random()),
+        Token::Uint(U256::from(65544)),
+    ]));
+    // `setApprovalForAll(address,bool)`
+    let mut set_approval_for_all = vec![0xa2, 0x2c, 0xb4, 0x65];
+    set_approval_for_all.extend(ethabi::encode(&[
+        Token::Address(H160::random()),
+        Token::Bool(true),
+    ]));
+    for data in [approve, set_approval_for_all] {
+        let error = calls_helper
+            .execute(&mut storage, nft_factory_address, data, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(3));
+        assert_eq!(
+            error.message,
+            "execution reverted: approvals are implicit on zkSync; use isApprovedForAll"
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that ERC20 `transfer` and `transferFrom` revert instead of returning nothing.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_transfer_reverts() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+
+    // `transfer(address,uint256)`
+    let mut transfer = vec![0xa9, 0x05, 0x9c, 0xbb];
+    transfer.extend(ethabi::encode(&[
+        Token::Address(H160::random()),
+        Token::Uint(U256::from(1)),
+    ]));
+    // `transferFrom(address,address,uint256)`
+    let mut transfer_from = vec![0x23, 0xb8, 0x72, 0xdd];
+    transfer_from.extend(ethabi::encode(&[
+        Token::Address(H160::random()),
+        Token::Address(H160::random()),
+        Token::Uint(U256::from(1)),
+    ]));
+    for data in [transfer, transfer_from] {
+        let error = calls_helper
+            .execute(&mut storage, token.address, data, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(3));
+        assert_eq!(
+            error.message,
+            "execution reverted: state-changing calls must be submitted as zkSync transactions"
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that ERC20 `increaseAllowance` and `decreaseAllowance` revert instead of returning nothing.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn erc20_allowance_mutators_revert() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+
+    // `increaseAllowance(address,uint256)` and `decreaseAllowance(address,uint256)`
+    for selector in [[0x39, 0x50, 0x93, 0x51], [0xa4, 0x57, 0xc2, 0xd7]] {
+        let mut data = selector.to_vec();
+        data.extend(ethabi::encode(&[
+            Token::Address(H160::random()),
+            Token::Uint(U256::from(1)),
+        ]));
+        let error = calls_helper
+            .execute(&mut storage, token.address, data, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(3));
+        assert_eq!(
+            error.message,
+            "execution reverted: allowances are implicit on zkSync and can't be changed; use allowance"
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that a function present in the ABI but missing in the emulation is reported
+/// as an error instead of a panic.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn unimplemented_function() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    let abi = r#"[{
+        "inputs": [],
+        "name": "unimplementedFunction",
+        "outputs": [],
+        "stateMutability": "view",
+        "type": "function"
+    }]"#;
+    let contract = ethabi::Contract::load(abi.as_bytes())?;
+    calls_helper.extend_nft_functions(CallsHelper::contract_functions(contract))?;
+
+    // `unimplementedFunction()`
+    let data = vec![0x3f, 0xa9, 0x16, 0xe7];
+    let error = calls_helper
+        .execute(&mut storage, nft_factory_address, data, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::ServerError(-32004));
+    assert_eq!(
+        error.message,
+        "function unimplementedFunction is not implemented"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+/// Tests that only the functions with an emulation are reported as supported.
+fn supported_functions() {
+    let mut calls_helper =
+        CallsHelper::new(&Web3Config::from_env(), Duration::from_secs(1)).unwrap();
+    let abi = r#"[{
+        "inputs": [],
+        "name": "unimplementedFunction",
+        "outputs": [],
+        "stateMutability": "view",
+        "type": "function"
+    }]"#;
+    let contract = ethabi::Contract::load(abi.as_bytes()).unwrap();
+    calls_helper
+        .extend_nft_functions(CallsHelper::contract_functions(contract))
+        .unwrap();
+
+    let functions = calls_helper.supported_functions();
+    for (kind, name) in [
+        (ContractKind::Erc20, "balanceOf"),
+        (ContractKind::Erc20, "decimals"),
+        (ContractKind::Nft, "ownerOf"),
+        (ContractKind::Nft, "tokenURI"),
+        (ContractKind::Nft, "balanceOf"),
+        (ContractKind::Erc1155, "balanceOfBatch"),
+        (ContractKind::Multicall3, "aggregate3"),
+    ] {
+        assert!(
+            functions.contains(&(kind, name.to_string())),
+            "{:?} {} is missing",
+            kind,
+            name
+        );
+    }
+    assert!(!functions
+        .iter()
+        .any(|(_, name)| name == "unimplementedFunction"));
+}
+
+/// Checks that `tokenURI` can be computed from the content hash without the storage,
+/// the CID settings and the prefix are the configured ones.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn content_hash_to_token_uri() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft = storage
+        .tokens_schema()
+        .get_nft(TokenId(65544))
+        .await?
+        .unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    let cases = [
+        (
+            CidVersion::V0,
+            "ipfs://".to_string(),
+            CallsHelper::ipfs_cid(Multihash::Sha256, nft.content_hash.as_bytes()).unwrap(),
+        ),
+        (
+            CidVersion::V1,
+            "https://ipfs.io/ipfs/".to_string(),
+            CallsHelper::ipfs_cid_v1(Multihash::Sha256, nft.content_hash.as_bytes()),
+        ),
+    ];
+    for (cid_version, prefix, cid) in cases {
+        let config = Web3Config {
+            nft_cid_version: cid_version,
+            nft_content_multihash: Multihash::Sha256,
+            nft_token_uri_format: TokenUriFormat::Ipfs,
+            nft_token_uri_prefix: prefix.clone(),
+            ..cfg.config.api.web3.clone()
+        };
+        let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+        let token_uri = calls_helper
+            .content_hash_to_token_uri(nft.content_hash)
+            .unwrap();
+        assert_eq!(token_uri, format!("{}{}", prefix, cid));
+
+        // `tokenURI(uint256)`
+        let mut data = vec![0xc8, 0x7b, 0x56, 0xdd];
+        data.extend(ethabi::encode(&[Token::Uint(U256::from(nft.id.0))]));
+        let resp_data = calls_helper
+            .execute(&mut storage, nft_factory_address, data, None, None)
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+        assert_eq!(outputs[0].clone().into_string().unwrap(), token_uri);
+    }
+
+    Ok(())
+}
+
+/// Checks that only the allowed tokens are emulated if the allow-list is set.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn call_allowed_tokens() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let allowed_token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let other_token = storage
+        .tokens_schema()
+        .get_token(TokenId(16).into())
+        .await?
+        .unwrap();
+    let zksync_proxy_address = H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap();
+    let config = Web3Config {
+        call_allowed_tokens: Some(vec![allowed_token.address]),
+        zksync_proxy_addresses: vec![zksync_proxy_address],
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+
+    // `decimals()`
+    let data = vec![0x31, 0x3c, 0xe5, 0x67];
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            allowed_token.address,
+            data.clone(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Uint(8)], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_uint().unwrap(),
+        U256::from(allowed_token.decimals)
+    );
+    // The token exists, but it isn't in the allow-list.
+    let resp_data = calls_helper
+        .execute(&mut storage, other_token.address, data, None, None)
+        .await
+        .unwrap();
+    assert!(resp_data.is_empty());
+    assert!(calls_helper
+        .get_code(&mut storage, other_token.address)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // The proxy doesn't need to be in the allow-list.
+    // `name()`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            zksync_proxy_address,
+            vec![0x06, 0xfd, 0xde, 0x03],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+    assert_eq!(
+        outputs[0].clone().into_string().unwrap(),
+        config.nft_collection_name
+    );
+
+    Ok(())
+}
+
+/// Checks that `decimals` and `symbol` of the genesis tokens are served without the storage.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn system_tokens() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let config = Web3Config {
+        system_tokens_network: Some("mainnet".to_string()),
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    // Mainnet DAI isn't stored in the test database, so it can only be found in the genesis list.
+    let dai_address = H160::from_str("6B175474E89094C44Da98b954EedeAC495271d0F").unwrap();
+    assert!(storage
+        .tokens_schema()
+        .get_token(dai_address.into())
+        .await?
+        .is_none());
+
+    // `decimals()`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            dai_address,
+            vec![0x31, 0x3c, 0xe5, 0x67],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Uint(8)], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_uint().unwrap(), U256::from(18));
+    // `symbol()`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            dai_address,
+            vec![0x95, 0xd8, 0x9b, 0x41],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_string().unwrap(), "DAI");
+
+    // Balances are still loaded from the storage, where the token is unknown.
+    // `balanceOf(address)`
+    let mut data = vec![0x70, 0xa0, 0x82, 0x31];
+    data.extend(ethabi::encode(&[Token::Address(H160::random())]));
+    let resp_data = calls_helper
+        .execute(&mut storage, dai_address, data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_uint().unwrap(), U256::zero());
+
+    Ok(())
+}
+
+/// Checks that the balance of an unknown token is zero, while other calls return nothing.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn unknown_token_balance() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let unknown_token = H160::random();
+
+    // `balanceOf(address)`
+    let mut data = vec![0x70, 0xa0, 0x82, 0x31];
+    data.extend(ethabi::encode(&[Token::Address(H160::random())]));
+    let resp_data = calls_helper
+        .execute(&mut storage, unknown_token, data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(&[ParamType::Uint(256)], &resp_data).unwrap();
+    assert_eq!(outputs[0].clone().into_uint().unwrap(), U256::zero());
+
+    // `decimals()`
+    let resp_data = calls_helper
+        .execute(
+            &mut storage,
+            unknown_token,
+            vec![0x31, 0x3c, 0xe5, 0x67],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(resp_data.is_empty());
+
+    Ok(())
+}
+
+/// Checks that arguments that can't be decoded are reported as errors.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn malformed_arguments() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let calls_helper = CallsHelper::new(&cfg.config.api.web3, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let token = storage
+        .tokens_schema()
+        .get_token(TokenId(1).into())
+        .await?
+        .unwrap();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let multicall3_address = H160::from_str(MULTICALL3_ADDRESS).unwrap();
+
+    for (to, data, message) in [
+        // `balanceOf(address)` with the truncated address.
+        (
+            token.address,
+            [vec![0x70, 0xa0, 0x82, 0x31], vec![0; 31]].concat(),
+            "cannot decode arguments of balanceOf: 31 bytes received",
+        ),
+        // `ownerOf(uint256)` without the token ID.
+        (
+            nft_factory_address,
+            vec![0x63, 0x52, 0x21, 0x1e],
+            "cannot decode arguments of ownerOf: 0 bytes received",
+        ),
+        // `aggregate3((address,bool,bytes)[])` with the offset pointing out of the calldata.
+        (
+            multicall3_address,
+            [vec![0x82, 0xad, 0x56, 0xcb], vec![0xff; 32]].concat(),
+            "cannot decode arguments of aggregate3: 32 bytes received",
+        ),
+    ] {
+        let error = calls_helper
+            .execute(&mut storage, to, data, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidParams);
+        assert_eq!(error.message, message);
+    }
+
+    Ok(())
+}
+
+/// Checks that `eth_getCode` reports code only for the emulated contracts.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn get_code() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let token = {
+        let mut storage = cfg.pool.access_storage().await?;
+        storage
+            .tokens_schema()
+            .get_token(TokenId(1).into())
+            .await?
+            .unwrap()
+    };
+    let zksync_proxy_address = H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap();
+    let minimal_proxy = |address: H160| {
+        format!(
+            "0x363d3d373d3d3d363d73{}5af43d82803e903d91602b57fd5bf3",
+            hex::encode(address)
+        )
+    };
+
+    for (address, expected_code) in [
+        (token.address, minimal_proxy(token.address)),
+        (zksync_proxy_address, minimal_proxy(zksync_proxy_address)),
+        (H160::random(), "0x".to_string()),
+        (H160::zero(), "0x".to_string()),
+    ] {
+        let fut = {
+            let (client, server) = local_client().await?;
+            join(
+                client.call_method(
+                    "eth_getCode",
+                    Params::Array(vec![
+                        Value::String(format!("{:#?}", address)),
+                        Value::String("latest".to_string()),
+                    ]),
+                ),
+                server,
+            )
+        };
+        let code = fut.await.0.unwrap();
+        assert_eq!(
+            serde_json::from_value::<String>(code).unwrap(),
+            expected_code
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that the NFT factory and the zkSync proxy report the configured collection.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn nft_collection_name_and_symbol() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let config = Web3Config {
+        nft_collection_name: "Test Collection".to_string(),
+        nft_collection_symbol: "TEST".to_string(),
+        ..cfg.config.api.web3.clone()
+    };
+    let calls_helper = CallsHelper::new(&config, Duration::from_secs(1))?;
+    let mut storage = cfg.pool.access_storage().await?;
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let zksync_proxy_address = H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap();
+
+    for to in [nft_factory_address, zksync_proxy_address] {
+        // `name()` and `symbol()`
+        for (data, expected) in [
+            (vec![0x06, 0xfd, 0xde, 0x03], "Test Collection"),
+            (vec![0x95, 0xd8, 0x9b, 0x41], "TEST"),
+        ] {
+            let resp_data = calls_helper
+                .execute(&mut storage, to, data, None, None)
+                .await
+                .unwrap();
+            let outputs = ethabi::decode(&[ParamType::String], &resp_data).unwrap();
+            assert_eq!(outputs[0].clone().into_string().unwrap(), expected);
+        }
+    }
+
+    Ok(())
+}
+
+/// In-memory storage of the emulated contracts, it has a single state, so the blocks
+/// are ignored.
+#[derive(Debug, Default)]
+struct MockStorage {
+    tokens: HashMap<H160, ZkSyncToken>,
+    token_names: HashMap<TokenId, String>,
+    nfts: HashMap<TokenId, NFT>,
+    nft_owners: HashMap<TokenId, AccountId>,
+    /// Block of the transfer to the current owner and the previous owner of the NFT,
+    /// who owns the NFT before that block.
+    nft_transfers: HashMap<TokenId, (BlockNumber, AccountId)>,
+    nft_approvals: HashMap<TokenId, H160>,
+    accounts: HashMap<AccountId, H160>,
+    balances: HashMap<(H160, TokenId), BigUint>,
+    last_verified_block: BlockNumber,
+    /// Number of the `last_verified_block` lookups so far.
+    block_lookups: usize,
+}
+
+impl MockStorage {
+    /// Owner of the NFT as of the block, `None` block is the latest state.
+    fn nft_owner(&self, token_id: TokenId, block: Option<BlockNumber>) -> Option<AccountId> {
+        match (self.nft_transfers.get(&token_id), block) {
+            (Some((transfer_block, previous_owner)), Some(block)) if block < *transfer_block => {
+                Some(*previous_owner)
+            }
+            _ => self.nft_owners.get(&token_id).copied(),
+        }
+    }
+
+    /// NFTs owned by the address as of the block in the ascending order of their IDs.
+    fn account_nfts(&self, address: H160, block: Option<BlockNumber>) -> Vec<TokenId> {
+        let mut token_ids: Vec<TokenId> = self
+            .nfts
+            .keys()
+            .filter(|token_id| {
+                let owner_id = self.nft_owner(**token_id, block);
+                owner_id.and_then(|owner_id| self.accounts.get(&owner_id)) == Some(&address)
+            })
+            .copied()
+            .collect();
+        token_ids.sort();
+        token_ids
+    }
+
+    fn minted_nfts(&self) -> Vec<TokenId> {
+        let mut token_ids: Vec<TokenId> = self.nfts.keys().copied().collect();
+        token_ids.sort();
+        token_ids
+    }
+}
+
+#[async_trait]
+impl TokenProvider for MockStorage {
+    async fn get_token(&mut self, address: H160) -> QueryResult<Option<ZkSyncToken>> {
+        Ok(self.tokens.get(&address).cloned())
+    }
+
+    async fn get_cached_token(
+        &mut self,
+        _tokens: &TokenDBCache,
+        address: H160,
+    ) -> QueryResult<Option<ZkSyncToken>> {
+        self.get_token(address).await
+    }
+
+    async fn get_token_name(&mut self, token_id: TokenId) -> QueryResult<Option<String>> {
+        Ok(self.token_names.get(&token_id).cloned())
+    }
+
+    async fn get_token_total_supply(&mut self, token_id: TokenId) -> QueryResult<BigUint> {
+        Ok(self
+            .balances
+            .iter()
+            .filter(|((_, id), _)| *id == token_id)
+            .map(|(_, balance)| balance)
+            .sum())
+    }
+
+    async fn get_account_balance_for_block(
+        &mut self,
+        address: H160,
+        _block: BlockNumber,
+        token_id: TokenId,
+    ) -> QueryResult<BigUint> {
+        Ok(self
+            .balances
+            .get(&(address, token_id))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn get_last_verified_confirmed_block(&mut self) -> QueryResult<BlockNumber> {
+        self.block_lookups += 1;
+        Ok(self.last_verified_block)
+    }
+}
+
+#[async_trait]
+impl NftProvider for MockStorage {
+    async fn get_nft(
+        &mut self,
+        _tokens: &TokenDBCache,
+        token_id: TokenId,
+    ) -> QueryResult<Option<NFT>> {
+        Ok(self.nfts.get(&token_id).cloned())
+    }
+
+    async fn get_nft_owner(
+        &mut self,
+        token_id: TokenId,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<Option<AccountId>> {
+        Ok(self.nft_owner(token_id, block))
+    }
+
+    async fn account_address_by_id(&mut self, account_id: AccountId) -> QueryResult<Option<H160>> {
+        Ok(self.accounts.get(&account_id).copied())
+    }
+
+    async fn get_nft_approved(&mut self, token_id: TokenId) -> QueryResult<Option<H160>> {
+        Ok(self.nft_approvals.get(&token_id).copied())
+    }
+
+    async fn get_nft_owners(
+        &mut self,
+        token_ids: &[TokenId],
+    ) -> QueryResult<Vec<(TokenId, Option<H160>)>> {
+        Ok(token_ids
+            .iter()
+            .filter(|token_id| self.nfts.contains_key(*token_id))
+            .map(|token_id| {
+                let owner = self
+                    .nft_owners
+                    .get(token_id)
+                    .and_then(|owner_id| self.accounts.get(owner_id))
+                    .copied();
+                (*token_id, owner)
+            })
+            .collect())
+    }
+
+    async fn get_account_nft_balance(
+        &mut self,
+        address: H160,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<u32> {
+        Ok(self.account_nfts(address, block).len() as u32)
+    }
+
+    async fn get_account_nft_by_index(
+        &mut self,
+        address: H160,
+        index: u32,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<Option<TokenId>> {
+        Ok(self
+            .account_nfts(address, block)
+            .get(index as usize)
+            .copied())
+    }
+
+    async fn get_account_nfts(
+        &mut self,
+        address: H160,
+        offset: u32,
+        limit: u32,
+        block: Option<BlockNumber>,
+    ) -> QueryResult<Vec<TokenId>> {
+        Ok(self
+            .account_nfts(address, block)
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn get_minted_nfts_count(&mut self, _block: BlockNumber) -> QueryResult<u32> {
+        Ok(self.nfts.len() as u32)
+    }
+
+    async fn get_minted_nft_by_index(
+        &mut self,
+        _block: BlockNumber,
+        index: u32,
+    ) -> QueryResult<Option<TokenId>> {
+        Ok(self.minted_nfts().get(index as usize).copied())
+    }
+}
+
+/// Addresses of the mock: the ERC20 token, the owner of its balance and of both NFTs,
+/// the holder of the rest of the balance and the approved operator of the second NFT.
+const MOCK_TOKEN: H160 = H160::repeat_byte(0x11);
+const MOCK_OWNER: H160 = H160::repeat_byte(0x22);
+const MOCK_HOLDER: H160 = H160::repeat_byte(0x33);
+const MOCK_OPERATOR: H160 = H160::repeat_byte(0x44);
+
+/// Builds the mock with the ERC20 token 1, split between the owner and the holder,
+/// and two NFTs of the owner.
+fn mock_storage() -> MockStorage {
+    let mut storage = MockStorage {
+        last_verified_block: BlockNumber(5),
+        ..Default::default()
+    };
+    storage.tokens.insert(
+        MOCK_TOKEN,
+        ZkSyncToken::new(TokenId(1), MOCK_TOKEN, "MLTT", 18, TokenKind::ERC20),
+    );
+    storage
+        .token_names
+        .insert(TokenId(1), "Multi Token".to_string());
+    storage.accounts.insert(AccountId(1), MOCK_OWNER);
+    storage.accounts.insert(AccountId(2), MOCK_HOLDER);
+    storage
+        .balances
+        .insert((MOCK_OWNER, TokenId(1)), BigUint::from(1000u32));
+    storage
+        .balances
+        .insert((MOCK_HOLDER, TokenId(1)), BigUint::from(500u32));
+    for (serial_id, token_id) in [MIN_NFT_TOKEN_ID, MIN_NFT_TOKEN_ID + 1].iter().enumerate() {
+        let token_id = TokenId(*token_id);
+        let nft = NFT::new(
+            token_id,
+            serial_id as u32,
+            AccountId(2),
+            MOCK_HOLDER,
+            H160::from_low_u64_be(token_id.0 as u64),
+            None,
+            H256::repeat_byte(serial_id as u8 + 1),
+        );
+        storage.tokens.insert(
+            nft.address,
+            ZkSyncToken::new(token_id, nft.address, &nft.symbol, 0, TokenKind::NFT),
+        );
+        storage.nfts.insert(token_id, nft);
+        storage.nft_owners.insert(token_id, AccountId(1));
+        storage
+            .balances
+            .insert((MOCK_OWNER, token_id), BigUint::from(1u32));
+    }
+    storage
+        .nft_approvals
+        .insert(TokenId(MIN_NFT_TOKEN_ID + 1), MOCK_OPERATOR);
+    storage
+}
+
+/// Config of the mock calls, the token metadata isn't decorated.
+fn mock_calls_helper() -> CallsHelper {
+    let config = Web3Config {
+        nft_collection_name: "Mock Collection".to_string(),
+        nft_collection_symbol: "MOCK".to_string(),
+        nft_total_supply: NftTotalSupply::LiveCount,
+        nft_max_token_id: None,
+        nft_get_approved_proxy: false,
+        erc20_logo_uri_pattern: None,
+        erc20_symbol_prefix: None,
+        erc20_symbol_suffix: None,
+        erc20_symbol_overrides: None,
+        erc20_allowance_policy: AllowancePolicy::ProxyOnlyMax,
+        call_allowed_tokens: None,
+        system_tokens_network: None,
+        ..Web3Config::from_env()
+    };
+    CallsHelper::new(&config, Duration::from_secs(1)).unwrap()
+}
+
+/// Dispatches the call of the function with the signature at the latest state of the mock.
+async fn mock_call(
+    calls_helper: &CallsHelper,
+    storage: &mut MockStorage,
+    to: H160,
+    signature: &str,
+    params: &[Token],
+) -> jsonrpc_core::Result<Vec<u8>> {
+    let mut data = CallsHelper::selector_for(signature).to_vec();
+    data.extend(ethabi::encode(params));
+    calls_helper
+        .dispatch_call(storage, to, &data, None, None, None)
+        .await?
+        .into_result()
+}
+
+/// Checks that the call reverts with the reason.
+fn assert_reverted(result: jsonrpc_core::Result<Vec<u8>>, reason: &str) {
+    let error = result.unwrap_err();
+    assert_eq!(error.code, ErrorCode::ServerError(3));
+    assert_eq!(error.message, format!("execution reverted: {}", reason));
+}
+
+fn decode_uint(output: &[u8]) -> U256 {
+    ethabi::decode(&[ParamType::Uint(256)], output).unwrap()[0]
+        .clone()
+        .into_uint()
+        .unwrap()
+}
+
+/// Checks every ERC20 function against the mock storage.
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_erc20_calls() {
+    let calls_helper = mock_calls_helper();
+    let mut storage = mock_storage();
+    let zksync_proxy_address = H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap();
+
+    for (signature, expected) in [("name()", "Multi Token"), ("symbol()", "MLTT")] {
+        let output = mock_call(&calls_helper, &mut storage, MOCK_TOKEN, signature, &[])
+            .await
+            .unwrap();
+        let outputs = ethabi::decode(&[ParamType::String], &output).unwrap();
+        assert_eq!(outputs[0].clone().into_string().unwrap(), expected);
+    }
+    let output = mock_call(&calls_helper, &mut storage, MOCK_TOKEN, "decimals()", &[])
+        .await
+        .unwrap();
+    assert_eq!(decode_uint(&output), U256::from(18));
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        MOCK_TOKEN,
+        "totalSupply()",
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(decode_uint(&output), U256::from(1500));
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        MOCK_TOKEN,
+        "balanceOf(address)",
+        &[Token::Address(MOCK_OWNER)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(decode_uint(&output), U256::from(1000));
+
+    for (spender, expected) in [
+        (zksync_proxy_address, U256::max_value()),
+        (MOCK_OPERATOR, U256::zero()),
+    ] {
+        let output = mock_call(
+            &calls_helper,
+            &mut storage,
+            MOCK_TOKEN,
+            "allowance(address,address)",
+            &[Token::Address(MOCK_OWNER), Token::Address(spender)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(decode_uint(&output), expected);
+    }
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        MOCK_TOKEN,
+        "nonces(address)",
+        &[Token::Address(MOCK_OWNER)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(decode_uint(&output), U256::zero());
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        MOCK_TOKEN,
+        "DOMAIN_SEPARATOR()",
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(output.len(), 32);
+    assert_ne!(output, vec![0; 32]);
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        MOCK_TOKEN,
+        "PERMIT_TYPEHASH()",
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        output,
+        tiny_keccak::keccak256(
+            b"Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)"
+        )
+        .to_vec()
+    );
+
+    let amount = Token::Uint(U256::from(1));
+    for (signature, params, reason) in [
+        (
+            "transfer(address,uint256)",
+            vec![Token::Address(MOCK_HOLDER), amount.clone()],
+            "state-changing calls must be submitted as zkSync transactions",
+        ),
+        (
+            "transferFrom(address,address,uint256)",
+            vec![
+                Token::Address(MOCK_OWNER),
+                Token::Address(MOCK_HOLDER),
+                amount.clone(),
+            ],
+            "state-changing calls must be submitted as zkSync transactions",
+        ),
+        (
+            "increaseAllowance(address,uint256)",
+            vec![Token::Address(MOCK_HOLDER), amount.clone()],
+            "allowances are implicit on zkSync and can't be changed; use allowance",
+        ),
+        (
+            "decreaseAllowance(address,uint256)",
+            vec![Token::Address(MOCK_HOLDER), amount],
+            "allowances are implicit on zkSync and can't be changed; use allowance",
+        ),
+    ] {
+        let result = mock_call(&calls_helper, &mut storage, MOCK_TOKEN, signature, &params).await;
+        assert_reverted(result, reason);
+    }
+}
+
+/// Checks the routing against the mock storage: nobody holds tokens that don't exist,
+/// other calls of them, including the empty ones, and unknown selectors aren't answered.
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_call_routes() {
+    let calls_helper = mock_calls_helper();
+    let mut storage = mock_storage();
+    let unknown_token = H160::repeat_byte(0x55);
+
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        unknown_token,
+        "balanceOf(address)",
+        &[Token::Address(MOCK_OWNER)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(decode_uint(&output), U256::zero());
+
+    let data = CallsHelper::selector_for("decimals()").to_vec();
+    let outcome = calls_helper
+        .dispatch_call(&mut storage, unknown_token, &data, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(outcome, ExecuteOutcome::MissingToken);
+
+    let selector = CallsHelper::selector_for("mint(address,uint256)");
+    let outcome = calls_helper
+        .dispatch_call(&mut storage, MOCK_TOKEN, &selector, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(outcome, ExecuteOutcome::UnknownSelector(selector));
+
+    // Empty data is routed as well, only the emulated contracts answer it.
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    for (to, expected) in [
+        (nft_factory_address, ExecuteOutcome::Answered(Vec::new())),
+        (MOCK_TOKEN, ExecuteOutcome::Answered(Vec::new())),
+        (unknown_token, ExecuteOutcome::MissingToken),
+    ] {
+        let outcome = calls_helper
+            .dispatch_call(&mut storage, to, &[], None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, expected, "{:?}", to);
+    }
+}
+
+/// Checks every NFT factory function against the mock storage.
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_nft_calls() {
+    let calls_helper = mock_calls_helper();
+    let mut storage = mock_storage();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let zksync_proxy_address = H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap();
+    let nft = storage.nfts[&TokenId(MIN_NFT_TOKEN_ID)].clone();
+    let token_id = Token::Uint(U256::from(nft.id.0));
+    let missing_token_id = Token::Uint(U256::from(MIN_NFT_TOKEN_ID + 2));
+
+    for (signature, expected) in [("name()", "Mock Collection"), ("symbol()", "MOCK")] {
+        let output = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            signature,
+            &[],
+        )
+        .await
+        .unwrap();
+        let outputs = ethabi::decode(&[ParamType::String], &output).unwrap();
+        assert_eq!(outputs[0].clone().into_string().unwrap(), expected);
+    }
+
+    let content_hash = Token::FixedBytes(nft.content_hash.as_bytes().to_vec());
+    let cid = calls_helper.token_cid(nft.content_hash.as_bytes()).unwrap();
+    let token_uri = calls_helper.nft_token_uri(&nft).unwrap();
+    for (signature, expected, reason) in [
+        (
+            "creatorId(uint256)",
+            vec![Token::Uint(U256::from(nft.creator_id.0))],
+            "creator ID query for nonexistent token",
+        ),
+        (
+            "creatorAddress(uint256)",
+            vec![Token::Address(nft.creator_address)],
+            "creator address query for nonexistent token",
+        ),
+        (
+            "getCreator(uint256)",
+            vec![
+                Token::Uint(U256::from(nft.creator_id.0)),
+                Token::Address(nft.creator_address),
+            ],
+            "creator query for nonexistent token",
+        ),
+        (
+            "getNftSummary(uint256)",
+            vec![
+                Token::Uint(U256::from(nft.creator_id.0)),
+                Token::Address(nft.creator_address),
+                Token::Uint(U256::from(nft.serial_id)),
+                content_hash.clone(),
+            ],
+            "summary query for nonexistent token",
+        ),
+        (
+            "serialId(uint256)",
+            vec![Token::Uint(U256::from(nft.serial_id))],
+            "serial ID query for nonexistent token",
+        ),
+        (
+            "contentHash(uint256)",
+            vec![content_hash],
+            "content hash query for nonexistent token",
+        ),
+        (
+            "contentHashCid(uint256)",
+            vec![Token::String(format!("ipfs://{}", cid))],
+            "content CID query for nonexistent token",
+        ),
+        (
+            "tokenURI(uint256)",
+            vec![Token::String(token_uri)],
+            "ERC721Metadata: URI query for nonexistent token",
+        ),
+        (
+            "getTokenAddress(uint256)",
+            vec![Token::Address(nft.address)],
+            "token address query for nonexistent token",
+        ),
+        (
+            "ownerOf(uint256)",
+            vec![Token::Address(MOCK_OWNER)],
+            "ERC721: owner query for nonexistent token",
+        ),
+        (
+            "getApproved(uint256)",
+            vec![Token::Address(H160::zero())],
+            "ERC721: approved query for nonexistent token",
+        ),
+    ] {
+        let output = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            signature,
+            &[token_id.clone()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, ethabi::encode(&expected), "{}", signature);
+        let result = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            signature,
+            &[missing_token_id.clone()],
+        )
+        .await;
+        assert_reverted(result, reason);
+    }
+
+    // The stored approval of the second NFT.
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "getApproved(uint256)",
+        &[Token::Uint(U256::from(MIN_NFT_TOKEN_ID + 1))],
+    )
+    .await
+    .unwrap();
+    assert_eq!(output, ethabi::encode(&[Token::Address(MOCK_OPERATOR)]));
+
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "getTokenId(address)",
+        &[Token::Address(nft.address)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(decode_uint(&output), U256::from(nft.id.0));
+    let result = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "getTokenId(address)",
+        &[Token::Address(MOCK_TOKEN)],
+    )
+    .await;
+    assert_reverted(result, "token ID query for nonexistent token");
+
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "balanceOf(address)",
+        &[Token::Address(MOCK_OWNER)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(decode_uint(&output), U256::from(2));
+    let result = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "balanceOf(address)",
+        &[Token::Address(H160::zero())],
+    )
+    .await;
+    assert_reverted(result, "ERC721: balance query for the zero address");
+
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "balancesOf(address,address[])",
+        &[
+            Token::Address(MOCK_OWNER),
+            Token::Array(vec![
+                Token::Address(MOCK_TOKEN),
+                Token::Address(H160::repeat_byte(0x55)),
+            ]),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        output,
+        ethabi::encode(&[Token::Array(vec![
+            Token::Uint(U256::from(1000)),
+            Token::Uint(U256::zero()),
+        ])])
+    );
+
+    for (operator, expected) in [(zksync_proxy_address, true), (MOCK_OPERATOR, false)] {
+        let output = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            "isApprovedForAll(address,address)",
+            &[Token::Address(MOCK_OWNER), Token::Address(operator)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, ethabi::encode(&[Token::Bool(expected)]));
+    }
+    for (signature, params) in [
+        (
+            "approve(address,uint256)",
+            vec![Token::Address(MOCK_OPERATOR), token_id],
+        ),
+        (
+            "setApprovalForAll(address,bool)",
+            vec![Token::Address(MOCK_OPERATOR), Token::Bool(true)],
+        ),
+    ] {
+        let result = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            signature,
+            &params,
+        )
+        .await;
+        assert_reverted(
+            result,
+            "approvals are implicit on zkSync; use isApprovedForAll",
+        );
+    }
+
+    for (address, expected) in [
+        (nft_factory_address, 2),
+        (MOCK_TOKEN, 1),
+        (H160::repeat_byte(0x55), 0),
+    ] {
+        let output = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            "getContractType(address)",
+            &[Token::Address(address)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(decode_uint(&output), U256::from(expected));
+    }
+
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "tokenInfo(address)",
+        &[Token::Address(MOCK_TOKEN)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        output,
+        ethabi::encode(&[
+            Token::String("Multi Token".to_string()),
+            Token::String("MLTT".to_string()),
+            Token::Uint(U256::from(18)),
+            Token::String(String::new()),
+        ])
+    );
+    let result = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "tokenInfo(address)",
+        &[Token::Address(nft.address)],
+    )
+    .await;
+    assert_reverted(result, "tokenInfo query for unknown token");
+}
+
+/// Checks the ERC1155 functions against the mock storage, only NFTs have balances.
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_erc1155_calls() {
+    let calls_helper = mock_calls_helper();
+    let mut storage = mock_storage();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    for (address, token_id, expected) in [
+        (MOCK_OWNER, MIN_NFT_TOKEN_ID, 1),
+        (MOCK_HOLDER, MIN_NFT_TOKEN_ID, 0),
+        (MOCK_OWNER, 1, 0),
+    ] {
+        let output = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            "balanceOf(address,uint256)",
+            &[Token::Address(address), Token::Uint(U256::from(token_id))],
+        )
+        .await
+        .unwrap();
+        assert_eq!(decode_uint(&output), U256::from(expected));
+    }
+    let result = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "balanceOf(address,uint256)",
+        &[
+            Token::Address(H160::zero()),
+            Token::Uint(U256::from(MIN_NFT_TOKEN_ID)),
+        ],
+    )
+    .await;
+    assert_reverted(result, "ERC1155: balance query for the zero address");
+
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "balanceOfBatch(address[],uint256[])",
+        &[
+            Token::Array(vec![
+                Token::Address(MOCK_OWNER),
+                Token::Address(MOCK_HOLDER),
+            ]),
+            Token::Array(vec![
+                Token::Uint(U256::from(MIN_NFT_TOKEN_ID + 1)),
+                Token::Uint(U256::from(MIN_NFT_TOKEN_ID + 1)),
+            ]),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        output,
+        ethabi::encode(&[Token::Array(vec![
+            Token::Uint(U256::from(1)),
+            Token::Uint(U256::zero()),
+        ])])
+    );
+    let result = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "balanceOfBatch(address[],uint256[])",
+        &[
+            Token::Array(vec![Token::Address(MOCK_OWNER)]),
+            Token::Array(Vec::new()),
+        ],
+    )
+    .await;
+    assert_reverted(result, "ERC1155: accounts and ids length mismatch");
+}
+
+/// Checks the ERC721Enumerable functions and `getOwnerTokens` against the mock storage,
+/// NFTs are enumerated in the ascending order of their IDs.
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_nft_enumeration_calls() {
+    let calls_helper = mock_calls_helper();
+    let mut storage = mock_storage();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    let output = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "totalSupply()",
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(decode_uint(&output), U256::from(2));
+    for index in 0..2u32 {
+        let output = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            "tokenByIndex(uint256)",
+            &[Token::Uint(U256::from(index))],
+        )
+        .await
+        .unwrap();
+        assert_eq!(decode_uint(&output), U256::from(MIN_NFT_TOKEN_ID + index));
+        let output = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            "tokenOfOwnerByIndex(address,uint256)",
+            &[Token::Address(MOCK_OWNER), Token::Uint(U256::from(index))],
+        )
+        .await
+        .unwrap();
+        assert_eq!(decode_uint(&output), U256::from(MIN_NFT_TOKEN_ID + index));
+    }
+    let result = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "tokenByIndex(uint256)",
+        &[Token::Uint(U256::from(2))],
+    )
+    .await;
+    assert_reverted(result, "ERC721Enumerable: global index out of bounds");
+    for (owner, index) in [(MOCK_OWNER, 2), (MOCK_HOLDER, 0)] {
+        let result = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            "tokenOfOwnerByIndex(address,uint256)",
+            &[Token::Address(owner), Token::Uint(U256::from(index))],
+        )
+        .await;
+        assert_reverted(result, "ERC721Enumerable: owner index out of bounds");
+    }
+
+    for (offset, limit, expected) in [
+        (0, 1, vec![MIN_NFT_TOKEN_ID]),
+        (1, 10, vec![MIN_NFT_TOKEN_ID + 1]),
+        (2, 10, Vec::new()),
+    ] {
+        let output = mock_call(
+            &calls_helper,
+            &mut storage,
+            nft_factory_address,
+            "getOwnerTokens(address,uint256,uint256)",
+            &[
+                Token::Address(MOCK_OWNER),
+                Token::Uint(U256::from(offset)),
+                Token::Uint(U256::from(limit)),
+            ],
+        )
+        .await
+        .unwrap();
+        let expected = expected
+            .into_iter()
+            .map(|token_id| Token::Uint(U256::from(token_id)))
+            .collect();
+        assert_eq!(output, ethabi::encode(&[Token::Array(expected)]));
+    }
+    let result = mock_call(
+        &calls_helper,
+        &mut storage,
+        nft_factory_address,
+        "getOwnerTokens(address,uint256,uint256)",
+        &[
+            Token::Address(MOCK_OWNER),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::from(101)),
+        ],
+    )
+    .await;
+    assert_reverted(result, "getOwnerTokens: limit exceeds 100");
+}
+
+/// Checks that `tokenOfOwnerByIndex` and `getOwnerTokens` enumerate the NFTs owned as of
+/// the requested block.
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_owner_nfts_for_block() {
+    let calls_helper = mock_calls_helper();
+    let mut storage = mock_storage();
+    // The first NFT was transferred from the holder to the owner in the third block.
+    storage
+        .nft_transfers
+        .insert(TokenId(MIN_NFT_TOKEN_ID), (BlockNumber(3), AccountId(2)));
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+
+    for (block, owner, expected) in [
+        (Some(BlockNumber(2)), MOCK_HOLDER, Some(MIN_NFT_TOKEN_ID)),
+        (Some(BlockNumber(2)), MOCK_OWNER, Some(MIN_NFT_TOKEN_ID + 1)),
+        (Some(BlockNumber(3)), MOCK_HOLDER, None),
+        (Some(BlockNumber(3)), MOCK_OWNER, Some(MIN_NFT_TOKEN_ID)),
+        (None, MOCK_OWNER, Some(MIN_NFT_TOKEN_ID)),
+    ] {
+        let mut data = CallsHelper::selector_for("tokenOfOwnerByIndex(address,uint256)").to_vec();
+        data.extend(ethabi::encode(&[
+            Token::Address(owner),
+            Token::Uint(U256::zero()),
+        ]));
+        let result = calls_helper
+            .dispatch_call(&mut storage, nft_factory_address, &data, block, None, None)
+            .await
+            .unwrap()
+            .into_result();
+        match expected {
+            Some(token_id) => assert_eq!(decode_uint(&result.unwrap()), U256::from(token_id)),
+            None => assert_reverted(result, "ERC721Enumerable: owner index out of bounds"),
+        }
+    }
+
+    for (block, owner, expected) in [
+        (Some(BlockNumber(2)), MOCK_HOLDER, vec![MIN_NFT_TOKEN_ID]),
+        (Some(BlockNumber(2)), MOCK_OWNER, vec![MIN_NFT_TOKEN_ID + 1]),
+        (Some(BlockNumber(3)), MOCK_HOLDER, Vec::new()),
+        (
+            None,
+            MOCK_OWNER,
+            vec![MIN_NFT_TOKEN_ID, MIN_NFT_TOKEN_ID + 1],
+        ),
+    ] {
+        let mut data =
+            CallsHelper::selector_for("getOwnerTokens(address,uint256,uint256)").to_vec();
+        data.extend(ethabi::encode(&[
+            Token::Address(owner),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::from(10)),
+        ]));
+        let output = calls_helper
+            .dispatch_call(&mut storage, nft_factory_address, &data, block, None, None)
+            .await
+            .unwrap()
+            .into_result()
+            .unwrap();
+        let expected = expected
+            .into_iter()
+            .map(|token_id| Token::Uint(U256::from(token_id)))
+            .collect();
+        assert_eq!(output, ethabi::encode(&[Token::Array(expected)]));
+    }
+}
+
+/// Checks `supportsInterface` of the NFT contracts against the mock storage.
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_supports_interface() {
+    let calls_helper = mock_calls_helper();
+    let mut storage = mock_storage();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let zksync_proxy_address = H160::from_str(ZKSYNC_PROXY_ADDRESS).unwrap();
+
+    // ERC165, ERC721, ERC721Metadata and unknown interfaces.
+    for (interface_id, expected) in [
+        ([0x01, 0xff, 0xc9, 0xa7], true),
+        ([0x80, 0xac, 0x58, 0xcd], true),
+        ([0x5b, 0x5e, 0x13, 0x9f], true),
+        ([0xff; 4], false),
+        ([0x00; 4], false),
+    ] {
+        for address in [nft_factory_address, zksync_proxy_address] {
+            let output = mock_call(
+                &calls_helper,
+                &mut storage,
+                address,
+                "supportsInterface(bytes4)",
+                &[Token::FixedBytes(interface_id.to_vec())],
+            )
+            .await
+            .unwrap();
+            assert_eq!(
+                output,
+                ethabi::encode(&[Token::Bool(expected)]),
+                "{}",
+                hex::encode(interface_id)
+            );
+        }
+    }
+}
+
+/// Checks `aggregate3` of the Multicall3 against the mock storage: the `ownerOf` sub-calls
+/// are answered by a single owners lookup, failures are reported by the sub-calls that
+/// allow them and revert the whole call otherwise.
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_multicall3_calls() {
+    let calls_helper = mock_calls_helper();
+    let mut storage = mock_storage();
+    let nft_factory_address = H160::from_str(NFT_FACTORY_ADDRESS).unwrap();
+    let call_data = |signature: &str, params: &[Token]| {
+        let mut data = CallsHelper::selector_for(signature).to_vec();
+        data.extend(ethabi::encode(params));
+        data
+    };
+    let aggregate3 = |calls: Vec<(H160, bool, Vec<u8>)>| {
+        let calls = calls
+            .into_iter()
+            .map(|(target, allow_failure, data)| {
+                Token::Tuple(vec![
+                    Token::Address(target),
+                    Token::Bool(allow_failure),
+                    Token::Bytes(data),
+                ])
+            })
+            .collect();
+        call_data("aggregate3((address,bool,bytes)[])", &[Token::Array(calls)])
+    };
+    let owner_of = |token_id: u32| call_data("ownerOf(uint256)", &[Token::Uint(token_id.into())]);
+
+    let data = aggregate3(vec![
+        (nft_factory_address, false, owner_of(MIN_NFT_TOKEN_ID)),
+        (nft_factory_address, true, owner_of(MIN_NFT_TOKEN_ID + 2)),
+        (
+            MOCK_TOKEN,
+            false,
+            call_data("balanceOf(address)", &[Token::Address(MOCK_HOLDER)]),
+        ),
+    ]);
+    let output = calls_helper
+        .aggregate3(&mut storage, &data, None, None)
+        .await
+        .unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])))],
+        &output,
+    )
+    .unwrap();
+    let results: Vec<(bool, Vec<u8>)> = outputs[0]
+        .clone()
+        .into_array()
+        .unwrap()
+        .into_iter()
+        .map(|result| {
+            let result = result.into_tuple().unwrap();
+            (
+                result[0].clone().into_bool().unwrap(),
+                result[1].clone().into_bytes().unwrap(),
+            )
+        })
+        .collect();
+    let mut revert_data = CallsHelper::selector_for("Error(string)").to_vec();
+    revert_data.extend(ethabi::encode(&[Token::String(
+        "ERC721: owner query for nonexistent token".to_string(),
+    )]));
+    assert_eq!(
+        results,
+        vec![
+            (true, ethabi::encode(&[Token::Address(MOCK_OWNER)])),
+            (false, revert_data),
+            (true, ethabi::encode(&[Token::Uint(U256::from(500))])),
+        ]
+    );
+    // Both `ownerOf` sub-calls are answered by the owners lookup, so the block is only
+    // looked up for the balance.
+    assert_eq!(storage.block_lookups, 1);
+
+    let data = aggregate3(vec![(
+        nft_factory_address,
+        false,
+        owner_of(MIN_NFT_TOKEN_ID + 2),
+    )]);
+    let result = calls_helper
+        .aggregate3(&mut storage, &data, None, None)
+        .await;
+    assert_reverted(result, "Multicall3: call failed");
+}
+
+/// Checks that `execute_batch` looks up the last verified block once for all balance calls,
+/// while a single call still looks it up on demand.
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_batch_block_lookup() {
+    let calls_helper = mock_calls_helper();
+    let mut storage = mock_storage();
+    let params = [Token::Address(MOCK_OWNER)];
+    let expected_balance = mock_call(
+        &calls_helper,
+        &mut storage,
+        MOCK_TOKEN,
+        "balanceOf(address)",
+        &params,
+    )
+    .await
+    .unwrap();
+    assert_eq!(storage.block_lookups, 1);
+
+    let mut data = CallsHelper::selector_for("balanceOf(address)").to_vec();
+    data.extend(ethabi::encode(&params));
+    let calls = vec![(MOCK_TOKEN, data); 100];
+    let results = calls_helper
+        .execute_batch(&mut storage, calls, None, None, None)
+        .await;
+    assert_eq!(storage.block_lookups, 2);
+    assert_eq!(results.len(), 100);
+    for result in results {
+        assert_eq!(result.unwrap(), expected_balance);
+    }
 }
@@ -260,6 +260,7 @@ pub enum Event {
     ZkSyncWithdrawNFT,
     ZkSyncSwap,
     ERCTransfer, // ERC20 and ERC721 transfers have same topics
+    ERCMetadataUpdate,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -6,7 +6,7 @@ use std::net::SocketAddr;
 use std::time::Duration;
 use zksync_utils::scaled_u64_to_ratio;
 // Workspace uses
-use zksync_types::AccountId;
+use zksync_types::{AccountId, Address};
 // Local uses
 use crate::envy_load;
 
@@ -231,6 +231,60 @@ pub struct Web3Config {
     /// Max difference between blocks in `eth_getLogs` method.
     pub max_block_range: u32,
     pub chain_id: u64,
+    /// Version of the IPFS CID returned by the `tokenURI` method of the NFT factory.
+    pub nft_cid_version: CidVersion,
+    /// Hash function used to compute the NFT content hashes, defines the multihash prefix of the CID.
+    pub nft_content_multihash: Multihash,
+    /// Prefix prepended to the IPFS CID in `tokenURI`, e.g. `ipfs://` or `https://cloudflare-ipfs.com/ipfs/`.
+    pub nft_token_uri_prefix: String,
+    /// Format of the value returned by the `tokenURI` method of the NFT factory.
+    pub nft_token_uri_format: TokenUriFormat,
+    /// If set, `tokenURI` of the NFTs without a content hash is this URI followed by the token ID,
+    /// e.g. `https://api.example.com/nft/`.
+    pub nft_base_token_uri: Option<String>,
+    /// Collection name returned by the `name` method of the NFT factory.
+    pub nft_collection_name: String,
+    /// Collection symbol returned by the `symbol` method of the NFT factory.
+    pub nft_collection_symbol: String,
+    /// Value returned by the `totalSupply` method of the NFT factory.
+    pub nft_total_supply: NftTotalSupply,
+    /// If set, `tokenInfo` of the NFT factory returns it as the token logo URI, with `{address}`
+    /// and `{symbol}` replaced by the token address and symbol. The logo URI is empty otherwise.
+    pub erc20_logo_uri_pattern: Option<String>,
+    /// If set, prepended to the ERC20 `symbol` and `name`, e.g. `zk` for the bridged tokens.
+    pub erc20_symbol_prefix: Option<String>,
+    /// If set, appended to the ERC20 `symbol` and `name`, e.g. `.e` for the bridged tokens.
+    pub erc20_symbol_suffix: Option<String>,
+    /// ERC20 `symbol` overrides as `address:symbol` pairs. The prefix and the suffix aren't
+    /// applied to these tokens, their `name` is returned as stored.
+    pub erc20_symbol_overrides: Option<Vec<String>>,
+    /// Value returned by the `allowance` method of ERC20 tokens.
+    pub erc20_allowance_policy: AllowancePolicy,
+    /// Max number of storage rows a single emulated call may scan, e.g. the index of `tokenByIndex`.
+    pub call_max_scanned_rows: u32,
+    /// Max size of the `eth_call` data in bytes, larger calls are rejected before decoding.
+    pub call_max_data_size: usize,
+    /// If set, the server fails to start if the ABIs contain functions without an emulation,
+    /// otherwise they are only logged and their calls fail.
+    pub call_strict_abi: bool,
+    /// Addresses of the zkSync proxy contracts, all of them are approved NFT operators.
+    pub zksync_proxy_addresses: Vec<Address>,
+    /// If set, only these ERC20 tokens are emulated, calls to other tokens return nothing.
+    pub call_allowed_tokens: Option<Vec<Address>>,
+    /// If set, `decimals` and `symbol` of the genesis tokens of this network (`etc/tokens/<network>.json`)
+    /// are returned without the storage access.
+    pub system_tokens_network: Option<String>,
+    /// If set, NFT IDs above it are treated as nonexistent without the storage access.
+    pub nft_max_token_id: Option<u32>,
+    /// If set, `getApproved` returns the first zkSync proxy instead of the zero address
+    /// (legacy behavior).
+    pub nft_get_approved_proxy: bool,
+    /// If set, NFT `Transfer` logs have the sender, the recipient and the token ID in the topics,
+    /// as in the canonical ERC721 event, rather than in the data.
+    pub nft_indexed_transfer_logs: bool,
+    /// If set, NFT mints are followed by the ERC4906 `MetadataUpdate` log of the minted token,
+    /// so the marketplaces load its metadata.
+    pub nft_metadata_update_logs: bool,
 }
 
 impl Web3Config {
@@ -239,6 +293,65 @@ impl Web3Config {
     }
 }
 
+/// Version of the IPFS content identifier.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CidVersion {
+    /// Base58 encoded multihash (`Qm...`).
+    V0,
+    /// Base32 encoded multibase CID with the `dag-pb` codec (`bafy...`).
+    V1,
+}
+
+/// Hash function of the NFT content hash, as defined by the multihash specification.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Multihash {
+    /// `sha2-256`, the default IPFS hash function.
+    Sha256,
+    /// `keccak-256`, the one used for Ethereum hashes.
+    Keccak256,
+    /// `blake2b-256`.
+    Blake2b256,
+    /// `blake2s-256`, used by the media-addressed NFTs.
+    Blake2s256,
+}
+
+/// Format of the NFT `tokenURI`.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenUriFormat {
+    /// IPFS CID of the content with the configured prefix (`ipfs://Qm...`).
+    Ipfs,
+    /// Base64 encoded ERC721 metadata JSON (`data:application/json;base64,...`)
+    /// with the IPFS link to the content as the image and the NFT creator as the attributes.
+    Data,
+}
+
+/// `totalSupply` of the NFT collection. Unlike ERC20 `totalSupply`, it's the number of NFTs
+/// rather than the sum of the balances, each NFT has the supply of one.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NftTotalSupply {
+    /// Number of NFTs minted by the verified blocks.
+    LiveCount,
+    /// Number of NFT IDs that can be minted, it never changes.
+    FixedMax,
+}
+
+/// Policy of the ERC20 `allowance` emulation. There are no allowances in zkSync,
+/// so the policy only defines what the integrations see.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowancePolicy {
+    /// Any amount is allowed for any spender.
+    AlwaysMax,
+    /// Nothing is allowed for any spender.
+    AlwaysZero,
+    /// Any amount is allowed for the zkSync proxies, nothing is allowed for other spenders.
+    ProxyOnlyMax,
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct PrometheusConfig {
     /// Port to which the Prometheus exporter server is listening.
@@ -248,7 +361,7 @@ pub struct PrometheusConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::configs::test_utils::set_env;
+    use crate::configs::test_utils::{addr, set_env};
     use std::net::IpAddr;
 
     fn expected_config() -> ApiConfig {
@@ -284,6 +397,34 @@ mod tests {
                 url: "http://127.0.0.1:3002".into(),
                 max_block_range: 10,
                 chain_id: 240,
+                nft_cid_version: CidVersion::V0,
+                nft_content_multihash: Multihash::Sha256,
+                nft_token_uri_prefix: "ipfs://".into(),
+                nft_token_uri_format: TokenUriFormat::Ipfs,
+                nft_base_token_uri: Some("https://api.example.com/nft/".into()),
+                nft_collection_name: "zkSync NFT".into(),
+                nft_collection_symbol: "ZKNFT".into(),
+                nft_total_supply: NftTotalSupply::FixedMax,
+                erc20_logo_uri_pattern: Some("https://cdn.example.com/tokens/{address}.png".into()),
+                erc20_symbol_prefix: Some("zk".into()),
+                erc20_symbol_suffix: None,
+                erc20_symbol_overrides: Some(vec![
+                    "0x2000000000000000000000000000000000000000:USDC.e".into(),
+                ]),
+                erc20_allowance_policy: AllowancePolicy::AlwaysMax,
+                call_max_scanned_rows: 100_000,
+                call_max_data_size: 131_072,
+                call_strict_abi: true,
+                zksync_proxy_addresses: vec![
+                    addr("1000000000000000000000000000000000000000"),
+                    addr("1000000000000000000000000000000000000001"),
+                ],
+                call_allowed_tokens: None,
+                system_tokens_network: None,
+                nft_max_token_id: None,
+                nft_get_approved_proxy: false,
+                nft_indexed_transfer_logs: true,
+                nft_metadata_update_logs: true,
             },
             private: PrivateApiConfig {
                 port: 8090,
@@ -327,6 +468,25 @@ API_WEB3_PORT="3002"
 API_WEB3_URL="http://127.0.0.1:3002"
 API_WEB3_CHAIN_ID="240"
 API_WEB3_MAX_BLOCK_RANGE="10"
+API_WEB3_NFT_CID_VERSION="v0"
+API_WEB3_NFT_CONTENT_MULTIHASH="sha256"
+API_WEB3_NFT_TOKEN_URI_PREFIX="ipfs://"
+API_WEB3_NFT_TOKEN_URI_FORMAT="ipfs"
+API_WEB3_NFT_BASE_TOKEN_URI="https://api.example.com/nft/"
+API_WEB3_NFT_COLLECTION_NAME="zkSync NFT"
+API_WEB3_NFT_COLLECTION_SYMBOL="ZKNFT"
+API_WEB3_NFT_TOTAL_SUPPLY="fixed_max"
+API_WEB3_ERC20_LOGO_URI_PATTERN="https://cdn.example.com/tokens/{address}.png"
+API_WEB3_ERC20_SYMBOL_PREFIX="zk"
+API_WEB3_ERC20_SYMBOL_OVERRIDES="0x2000000000000000000000000000000000000000:USDC.e"
+API_WEB3_ERC20_ALLOWANCE_POLICY="always_max"
+API_WEB3_CALL_MAX_SCANNED_ROWS="100000"
+API_WEB3_CALL_MAX_DATA_SIZE="131072"
+API_WEB3_CALL_STRICT_ABI="true"
+API_WEB3_ZKSYNC_PROXY_ADDRESSES="0x1000000000000000000000000000000000000000,0x1000000000000000000000000000000000000001"
+API_WEB3_NFT_GET_APPROVED_PROXY="false"
+API_WEB3_NFT_INDEXED_TRANSFER_LOGS="true"
+API_WEB3_NFT_METADATA_UPDATE_LOGS="true"
 API_PRIVATE_PORT="8090"
 API_PRIVATE_URL="http://127.0.0.1:8090"
 API_PROVER_PORT="8088"
@@ -0,0 +1,4 @@
+CREATE TABLE nft_approvals (
+    token_id INTEGER NOT NULL PRIMARY KEY,
+    operator BYTEA NOT NULL
+);
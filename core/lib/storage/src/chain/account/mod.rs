@@ -486,6 +486,8 @@ impl<'a, 'c> AccountSchema<'a, 'c> {
         Ok(BlockNumber(block_number as u32))
     }
 
+    /// Returns the balance of the token as of the given block. Unknown accounts and accounts
+    /// without balance updates of the token have zero balance, only the database errors fail.
     pub async fn get_account_balance_for_block(
         &mut self,
         address: Address,
@@ -575,6 +577,277 @@ impl<'a, 'c> AccountSchema<'a, 'c> {
         Ok(balance)
     }
 
+    /// Returns the number of NFTs owned by the address as of the given block.
+    pub async fn get_account_nft_balance_for_block(
+        &mut self,
+        address: Address,
+        block_number: BlockNumber,
+    ) -> QueryResult<u32> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let account_id = transaction
+            .chain()
+            .account_schema()
+            .account_id_by_address(address)
+            .await?;
+        let account_id = match account_id {
+            Some(id) => id,
+            None => {
+                return Ok(0);
+            }
+        };
+        if account_id == NFT_STORAGE_ACCOUNT_ID {
+            // It is special account ID, just return 0 for it.
+            return Ok(0);
+        }
+
+        let balance = sqlx::query!(
+            r#"
+                SELECT COUNT(*) as "count!" FROM (
+                    SELECT DISTINCT ON (coin_id) new_balance FROM account_balance_updates
+                    WHERE account_id = $1 AND block_number <= $2 AND coin_id >= $3 AND coin_id < $4
+                    ORDER BY coin_id, block_number DESC, update_order_id DESC
+                ) nft_balances
+                WHERE new_balance = 1
+            "#,
+            i64::from(account_id.0),
+            i64::from(block_number.0),
+            MIN_NFT_TOKEN_ID as i32,
+            NFT_TOKEN_ID.0 as i32
+        )
+        .fetch_one(transaction.conn())
+        .await?
+        .count as u32;
+
+        transaction.commit().await?;
+        metrics::histogram!(
+            "sql.chain.account.get_account_nft_balance_for_block",
+            start.elapsed()
+        );
+
+        Ok(balance)
+    }
+
+    /// Returns the id of the NFT owned by the address at the given index.
+    /// NFTs of the account are ordered by their token id.
+    pub async fn get_account_nft_by_index(
+        &mut self,
+        address: Address,
+        index: u32,
+    ) -> QueryResult<Option<TokenId>> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let account_id = transaction
+            .chain()
+            .account_schema()
+            .account_id_by_address(address)
+            .await?;
+        let account_id = match account_id {
+            Some(id) => id,
+            None => {
+                return Ok(None);
+            }
+        };
+        if account_id == NFT_STORAGE_ACCOUNT_ID {
+            // It is special account ID, it doesn't own any NFTs.
+            return Ok(None);
+        }
+
+        let token_id = sqlx::query!(
+            r#"
+                SELECT coin_id FROM balances
+                WHERE account_id = $1 AND coin_id >= $2 AND coin_id < $3 AND balance = 1
+                ORDER BY coin_id
+                OFFSET $4 LIMIT 1
+            "#,
+            i64::from(account_id.0),
+            MIN_NFT_TOKEN_ID as i32,
+            NFT_TOKEN_ID.0 as i32,
+            i64::from(index)
+        )
+        .fetch_optional(transaction.conn())
+        .await?
+        .map(|record| TokenId(record.coin_id as u32));
+
+        transaction.commit().await?;
+        metrics::histogram!(
+            "sql.chain.account.get_account_nft_by_index",
+            start.elapsed()
+        );
+
+        Ok(token_id)
+    }
+
+    /// Same as `get_account_nft_by_index`, but the NFTs are owned as of the given block.
+    pub async fn get_account_nft_by_index_for_block(
+        &mut self,
+        address: Address,
+        index: u32,
+        block_number: BlockNumber,
+    ) -> QueryResult<Option<TokenId>> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let account_id = transaction
+            .chain()
+            .account_schema()
+            .account_id_by_address(address)
+            .await?;
+        let account_id = match account_id {
+            Some(id) => id,
+            None => {
+                return Ok(None);
+            }
+        };
+        if account_id == NFT_STORAGE_ACCOUNT_ID {
+            // It is special account ID, it doesn't own any NFTs.
+            return Ok(None);
+        }
+
+        let token_id = sqlx::query!(
+            r#"
+                SELECT coin_id as "coin_id!" FROM (
+                    SELECT DISTINCT ON (coin_id) coin_id, new_balance FROM account_balance_updates
+                    WHERE account_id = $1 AND block_number <= $2 AND coin_id >= $3 AND coin_id < $4
+                    ORDER BY coin_id, block_number DESC, update_order_id DESC
+                ) nft_balances
+                WHERE new_balance = 1
+                ORDER BY coin_id
+                OFFSET $5 LIMIT 1
+            "#,
+            i64::from(account_id.0),
+            i64::from(block_number.0),
+            MIN_NFT_TOKEN_ID as i32,
+            NFT_TOKEN_ID.0 as i32,
+            i64::from(index)
+        )
+        .fetch_optional(transaction.conn())
+        .await?
+        .map(|record| TokenId(record.coin_id as u32));
+
+        transaction.commit().await?;
+        metrics::histogram!(
+            "sql.chain.account.get_account_nft_by_index_for_block",
+            start.elapsed()
+        );
+
+        Ok(token_id)
+    }
+
+    /// Returns the ids of the NFTs owned by the address, ordered by the token id
+    /// the same way as in `get_account_nft_by_index`.
+    pub async fn get_account_nfts(
+        &mut self,
+        address: Address,
+        offset: u32,
+        limit: u32,
+    ) -> QueryResult<Vec<TokenId>> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let account_id = transaction
+            .chain()
+            .account_schema()
+            .account_id_by_address(address)
+            .await?;
+        let account_id = match account_id {
+            Some(id) => id,
+            None => {
+                return Ok(Vec::new());
+            }
+        };
+        if account_id == NFT_STORAGE_ACCOUNT_ID {
+            // It is special account ID, it doesn't own any NFTs.
+            return Ok(Vec::new());
+        }
+
+        let token_ids = sqlx::query!(
+            r#"
+                SELECT coin_id FROM balances
+                WHERE account_id = $1 AND coin_id >= $2 AND coin_id < $3 AND balance = 1
+                ORDER BY coin_id
+                OFFSET $4 LIMIT $5
+            "#,
+            i64::from(account_id.0),
+            MIN_NFT_TOKEN_ID as i32,
+            NFT_TOKEN_ID.0 as i32,
+            i64::from(offset),
+            i64::from(limit)
+        )
+        .fetch_all(transaction.conn())
+        .await?
+        .into_iter()
+        .map(|record| TokenId(record.coin_id as u32))
+        .collect();
+
+        transaction.commit().await?;
+        metrics::histogram!("sql.chain.account.get_account_nfts", start.elapsed());
+
+        Ok(token_ids)
+    }
+
+    /// Same as `get_account_nfts`, but the NFTs are owned as of the given block.
+    pub async fn get_account_nfts_for_block(
+        &mut self,
+        address: Address,
+        offset: u32,
+        limit: u32,
+        block_number: BlockNumber,
+    ) -> QueryResult<Vec<TokenId>> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let account_id = transaction
+            .chain()
+            .account_schema()
+            .account_id_by_address(address)
+            .await?;
+        let account_id = match account_id {
+            Some(id) => id,
+            None => {
+                return Ok(Vec::new());
+            }
+        };
+        if account_id == NFT_STORAGE_ACCOUNT_ID {
+            // It is special account ID, it doesn't own any NFTs.
+            return Ok(Vec::new());
+        }
+
+        let token_ids = sqlx::query!(
+            r#"
+                SELECT coin_id as "coin_id!" FROM (
+                    SELECT DISTINCT ON (coin_id) coin_id, new_balance FROM account_balance_updates
+                    WHERE account_id = $1 AND block_number <= $2 AND coin_id >= $3 AND coin_id < $4
+                    ORDER BY coin_id, block_number DESC, update_order_id DESC
+                ) nft_balances
+                WHERE new_balance = 1
+                ORDER BY coin_id
+                OFFSET $5 LIMIT $6
+            "#,
+            i64::from(account_id.0),
+            i64::from(block_number.0),
+            MIN_NFT_TOKEN_ID as i32,
+            NFT_TOKEN_ID.0 as i32,
+            i64::from(offset),
+            i64::from(limit)
+        )
+        .fetch_all(transaction.conn())
+        .await?
+        .into_iter()
+        .map(|record| TokenId(record.coin_id as u32))
+        .collect();
+
+        transaction.commit().await?;
+        metrics::histogram!(
+            "sql.chain.account.get_account_nfts_for_block",
+            start.elapsed()
+        );
+
+        Ok(token_ids)
+    }
+
     pub async fn get_nft_owner(&mut self, token_id: TokenId) -> QueryResult<Option<AccountId>> {
         let start = Instant::now();
 
@@ -593,4 +866,125 @@ impl<'a, 'c> AccountSchema<'a, 'c> {
         metrics::histogram!("sql.chain.account.get_nft_owner", start.elapsed());
         Ok(owner_id)
     }
+
+    /// Returns the owners of the existing NFTs among the given ones by a single query,
+    /// nonexistent NFTs are skipped. NFTs without an owner (e.g. withdrawn ones) have no address.
+    pub async fn get_nft_owners(
+        &mut self,
+        token_ids: &[TokenId],
+    ) -> QueryResult<Vec<(TokenId, Option<Address>)>> {
+        let start = Instant::now();
+
+        let token_ids: Vec<i32> = token_ids.iter().map(|id| id.0 as i32).collect();
+        let records = sqlx::query!(
+            r#"
+                SELECT nft.token_id, (
+                    SELECT account_creates.address FROM balances
+                    INNER JOIN account_creates ON account_creates.account_id = balances.account_id
+                    WHERE balances.coin_id = nft.token_id AND balances.balance = 1
+                        AND balances.account_id != $2
+                    LIMIT 1
+                ) as "address?"
+                FROM nft
+                WHERE nft.token_id = ANY($1)
+            "#,
+            &token_ids,
+            i64::from(NFT_STORAGE_ACCOUNT_ID.0)
+        )
+        .fetch_all(self.0.conn())
+        .await?;
+        let owners = records
+            .into_iter()
+            .map(|record| {
+                (
+                    TokenId(record.token_id as u32),
+                    record.address.map(|address| Address::from_slice(&address)),
+                )
+            })
+            .collect();
+
+        metrics::histogram!("sql.chain.account.get_nft_owners", start.elapsed());
+        Ok(owners)
+    }
+
+    /// Returns the owner of the NFT as of the given block.
+    pub async fn get_nft_owner_for_block(
+        &mut self,
+        token_id: TokenId,
+        block_number: BlockNumber,
+    ) -> QueryResult<Option<AccountId>> {
+        let start = Instant::now();
+
+        let record = sqlx::query!(
+            r#"
+                SELECT account_id as "account_id!" FROM (
+                    SELECT DISTINCT ON (account_id) account_id, new_balance FROM account_balance_updates
+                    WHERE coin_id = $1 AND block_number <= $2 AND account_id != $3
+                    ORDER BY account_id, block_number DESC, update_order_id DESC
+                ) nft_balances
+                WHERE new_balance = 1
+                LIMIT 1
+            "#,
+            token_id.0 as i32,
+            i64::from(block_number.0),
+            i64::from(NFT_STORAGE_ACCOUNT_ID.0)
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+        let owner_id = record.map(|record| AccountId(record.account_id as u32));
+
+        metrics::histogram!("sql.chain.account.get_nft_owner_for_block", start.elapsed());
+        Ok(owner_id)
+    }
+
+    /// Returns the total amount of the token held by all accounts in the verified state.
+    pub async fn get_token_total_supply(&mut self, token_id: TokenId) -> QueryResult<BigUint> {
+        let start = Instant::now();
+
+        let total_supply = sqlx::query!(
+            r#"
+                SELECT COALESCE(SUM(balance), 0) as "total_supply!" FROM balances
+                WHERE coin_id = $1
+            "#,
+            token_id.0 as i32
+        )
+        .fetch_one(self.0.conn())
+        .await?
+        .total_supply;
+        let total_supply = total_supply.to_bigint().unwrap().to_biguint().unwrap();
+
+        metrics::histogram!("sql.chain.account.get_token_total_supply", start.elapsed());
+        Ok(total_supply)
+    }
+
+    /// Returns the addresses of the accounts with a nonzero balance of the token
+    /// in the verified state, ordered by the account ID.
+    pub async fn get_token_holders(
+        &mut self,
+        token_id: TokenId,
+        limit: u32,
+    ) -> QueryResult<Vec<Address>> {
+        let start = Instant::now();
+
+        let records = sqlx::query!(
+            r#"
+                SELECT accounts.address FROM balances
+                INNER JOIN accounts ON accounts.id = balances.account_id
+                WHERE balances.coin_id = $1 AND balances.balance > 0
+                ORDER BY balances.account_id
+                LIMIT $2
+            "#,
+            token_id.0 as i32,
+            i64::from(limit)
+        )
+        .fetch_all(self.0.conn())
+        .await?;
+        let holders = records
+            .into_iter()
+            .map(|record| Address::from_slice(&record.address))
+            .collect();
+
+        metrics::histogram!("sql.chain.account.get_token_holders", start.elapsed());
+        Ok(holders)
+    }
 }
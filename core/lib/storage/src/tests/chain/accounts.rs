@@ -425,6 +425,243 @@ async fn test_get_account_nft_balance(mut storage: StorageProcessor<'_>) -> Quer
     Ok(())
 }
 
+/// Checks that NFTs of the account are enumerated by their token id and that
+/// the indices of already owned NFTs don't change in later blocks.
+#[db_test]
+async fn test_get_account_nft_by_index(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let address = Address::random();
+    let nft_ids = [
+        TokenId(MIN_NFT_TOKEN_ID + 100),
+        TokenId(MIN_NFT_TOKEN_ID + 101),
+    ];
+
+    for nft_id in nft_ids {
+        storage
+            .tokens_schema()
+            .store_or_update_token(Token {
+                id: nft_id,
+                address: Address::random(),
+                symbol: "NFT".to_string(),
+                decimals: 0,
+                kind: TokenKind::NFT,
+                is_nft: true,
+            })
+            .await?;
+    }
+
+    // Checks that nonexistent account has no NFTs.
+    let nft = storage
+        .chain()
+        .account_schema()
+        .get_account_nft_by_index(address, 0)
+        .await?;
+    assert!(nft.is_none());
+
+    let updates1 = vec![
+        (
+            AccountId(1),
+            AccountUpdate::Create {
+                address,
+                nonce: Nonce(0),
+            },
+        ),
+        (
+            AccountId(1),
+            AccountUpdate::UpdateBalance {
+                old_nonce: Nonce(0),
+                new_nonce: Nonce(1),
+                balance_update: (nft_ids[0], BigUint::zero(), BigUint::from(1u32)),
+            },
+        ),
+    ];
+    storage
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(1), &updates1, 0)
+        .await?;
+    storage
+        .chain()
+        .state_schema()
+        .apply_state_update(BlockNumber(1))
+        .await?;
+
+    let nft = storage
+        .chain()
+        .account_schema()
+        .get_account_nft_by_index(address, 0)
+        .await?;
+    assert_eq!(nft, Some(nft_ids[0]));
+    let nft = storage
+        .chain()
+        .account_schema()
+        .get_account_nft_by_index(address, 1)
+        .await?;
+    assert!(nft.is_none());
+
+    let updates2 = vec![(
+        AccountId(1),
+        AccountUpdate::UpdateBalance {
+            old_nonce: Nonce(1),
+            new_nonce: Nonce(2),
+            balance_update: (nft_ids[1], BigUint::zero(), BigUint::from(1u32)),
+        },
+    )];
+    storage
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(2), &updates2, updates1.len())
+        .await?;
+    storage
+        .chain()
+        .state_schema()
+        .apply_state_update(BlockNumber(2))
+        .await?;
+
+    // Checks that the previously owned NFT keeps its index.
+    for (index, nft_id) in nft_ids.iter().enumerate() {
+        let nft = storage
+            .chain()
+            .account_schema()
+            .get_account_nft_by_index(address, index as u32)
+            .await?;
+        assert_eq!(nft, Some(*nft_id));
+    }
+    let nft = storage
+        .chain()
+        .account_schema()
+        .get_account_nft_by_index(address, 2)
+        .await?;
+    assert!(nft.is_none());
+
+    Ok(())
+}
+
+/// Checks that the total supply of the token is the sum of all account balances.
+#[db_test]
+async fn test_get_token_total_supply(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let token_id = TokenId(1);
+    storage
+        .tokens_schema()
+        .store_or_update_token(Token {
+            id: token_id,
+            address: Address::random(),
+            symbol: "DAI".to_string(),
+            decimals: 18,
+            kind: TokenKind::ERC20,
+            is_nft: false,
+        })
+        .await?;
+
+    // Checks that the token nobody holds has zero supply.
+    let total_supply = storage
+        .chain()
+        .account_schema()
+        .get_token_total_supply(token_id)
+        .await?;
+    assert_eq!(total_supply, BigUint::zero());
+
+    let mut updates = Vec::new();
+    for (account_id, balance) in [(AccountId(1), 100u32), (AccountId(2), 200u32)] {
+        updates.push((
+            account_id,
+            AccountUpdate::Create {
+                address: Address::random(),
+                nonce: Nonce(0),
+            },
+        ));
+        updates.push((
+            account_id,
+            AccountUpdate::UpdateBalance {
+                old_nonce: Nonce(0),
+                new_nonce: Nonce(0),
+                balance_update: (token_id, BigUint::zero(), BigUint::from(balance)),
+            },
+        ));
+    }
+    storage
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(1), &updates, 0)
+        .await?;
+    storage
+        .chain()
+        .state_schema()
+        .apply_state_update(BlockNumber(1))
+        .await?;
+
+    let total_supply = storage
+        .chain()
+        .account_schema()
+        .get_token_total_supply(token_id)
+        .await?;
+    assert_eq!(total_supply, BigUint::from(300u32));
+
+    Ok(())
+}
+
+/// Checks that only the accounts with a nonzero balance are the token holders.
+#[db_test]
+async fn test_get_token_holders(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let token_id = TokenId(1);
+    storage
+        .tokens_schema()
+        .store_or_update_token(Token {
+            id: token_id,
+            address: Address::random(),
+            symbol: "DAI".to_string(),
+            decimals: 18,
+            kind: TokenKind::ERC20,
+            is_nft: false,
+        })
+        .await?;
+
+    let addresses = [Address::random(), Address::random(), Address::random()];
+    let mut updates = Vec::new();
+    for (account_id, (address, balance)) in addresses.iter().zip([0u32, 100, 200]).enumerate() {
+        let account_id = AccountId(account_id as u32 + 1);
+        updates.push((
+            account_id,
+            AccountUpdate::Create {
+                address: *address,
+                nonce: Nonce(0),
+            },
+        ));
+        updates.push((
+            account_id,
+            AccountUpdate::UpdateBalance {
+                old_nonce: Nonce(0),
+                new_nonce: Nonce(0),
+                balance_update: (token_id, BigUint::zero(), BigUint::from(balance)),
+            },
+        ));
+    }
+    storage
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(1), &updates, 0)
+        .await?;
+    storage
+        .chain()
+        .state_schema()
+        .apply_state_update(BlockNumber(1))
+        .await?;
+
+    let holders = storage
+        .chain()
+        .account_schema()
+        .get_token_holders(token_id, 10)
+        .await?;
+    assert_eq!(holders, addresses[1..]);
+    let holders = storage
+        .chain()
+        .account_schema()
+        .get_token_holders(token_id, 1)
+        .await?;
+    assert_eq!(holders, addresses[1..2]);
+
+    Ok(())
+}
+
 #[db_test]
 async fn test_get_nft_owner(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
     let account_id1 = AccountId(1);
@@ -534,3 +771,132 @@ async fn test_get_nft_owner(mut storage: StorageProcessor<'_>) -> QueryResult<()
 
     Ok(())
 }
+
+/// Checks that NFT ownership can be reconstructed at the past blocks.
+#[db_test]
+async fn test_nft_ownership_for_block(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let account_id1 = AccountId(1);
+    let account_id2 = AccountId(2);
+    let address1 = Address::random();
+    let address2 = Address::random();
+    let nft_id = TokenId(MIN_NFT_TOKEN_ID + 100);
+
+    storage
+        .tokens_schema()
+        .store_or_update_token(Token {
+            id: nft_id,
+            address: Address::random(),
+            symbol: "NFT".to_string(),
+            decimals: 0,
+            kind: TokenKind::NFT,
+            is_nft: true,
+        })
+        .await?;
+
+    // Mint the NFT to the first account in the first block.
+    let updates1 = vec![
+        (
+            account_id1,
+            AccountUpdate::Create {
+                address: address1,
+                nonce: Nonce(0),
+            },
+        ),
+        (
+            account_id1,
+            AccountUpdate::UpdateBalance {
+                old_nonce: Nonce(0),
+                new_nonce: Nonce(1),
+                balance_update: (nft_id, BigUint::zero(), BigUint::from(1u32)),
+            },
+        ),
+    ];
+    storage
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(1), &updates1, 0)
+        .await?;
+
+    // Transfer the NFT to the second account in the second block.
+    let updates2 = vec![
+        (
+            account_id2,
+            AccountUpdate::Create {
+                address: address2,
+                nonce: Nonce(0),
+            },
+        ),
+        (
+            account_id1,
+            AccountUpdate::UpdateBalance {
+                old_nonce: Nonce(1),
+                new_nonce: Nonce(2),
+                balance_update: (nft_id, BigUint::from(1u32), BigUint::zero()),
+            },
+        ),
+        (
+            account_id2,
+            AccountUpdate::UpdateBalance {
+                old_nonce: Nonce(0),
+                new_nonce: Nonce(1),
+                balance_update: (nft_id, BigUint::zero(), BigUint::from(1u32)),
+            },
+        ),
+    ];
+    storage
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(2), &updates2, updates1.len())
+        .await?;
+    for block_number in 1..=2 {
+        storage
+            .chain()
+            .state_schema()
+            .apply_state_update(BlockNumber(block_number))
+            .await?;
+    }
+
+    let expected = [
+        (BlockNumber(0), None, 0, 0),
+        (BlockNumber(1), Some(account_id1), 1, 0),
+        (BlockNumber(2), Some(account_id2), 0, 1),
+    ];
+    for (block_number, owner, balance1, balance2) in expected {
+        let mut account_schema = storage.chain().account_schema();
+        assert_eq!(
+            account_schema
+                .get_nft_owner_for_block(nft_id, block_number)
+                .await?,
+            owner
+        );
+        assert_eq!(
+            account_schema
+                .get_account_nft_balance_for_block(address1, block_number)
+                .await?,
+            balance1
+        );
+        assert_eq!(
+            account_schema
+                .get_account_nft_balance_for_block(address2, block_number)
+                .await?,
+            balance2
+        );
+        for (address, balance) in [(address1, balance1), (address2, balance2)] {
+            let expected = if balance == 1 { Some(nft_id) } else { None };
+            assert_eq!(
+                account_schema
+                    .get_account_nft_by_index_for_block(address, 0, block_number)
+                    .await?,
+                expected
+            );
+            assert_eq!(
+                account_schema
+                    .get_account_nfts_for_block(address, 0, 10, block_number)
+                    .await?,
+                expected.into_iter().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -135,6 +135,158 @@ async fn tokens_storage(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
     Ok(())
 }
 
+/// Checks that the token name can be stored and loaded.
+#[db_test]
+async fn token_name(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    assert_eq!(
+        storage.tokens_schema().get_token_name(TokenId(0)).await?,
+        None
+    );
+    storage
+        .tokens_schema()
+        .store_token_name(TokenId(0), "Ether")
+        .await?;
+    assert_eq!(
+        storage.tokens_schema().get_token_name(TokenId(0)).await?,
+        Some("Ether".to_string())
+    );
+    // Unknown tokens have no name.
+    assert_eq!(
+        storage.tokens_schema().get_token_name(TokenId(100)).await?,
+        None
+    );
+    Ok(())
+}
+
+/// Checks that the NFT approval can be stored, replaced and loaded.
+#[db_test]
+async fn nft_approval(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    assert_eq!(
+        storage
+            .tokens_schema()
+            .get_nft_approved(TokenId(65536))
+            .await?,
+        None
+    );
+    for operator in [Address::random(), Address::random()] {
+        storage
+            .tokens_schema()
+            .store_nft_approval(TokenId(65536), operator)
+            .await?;
+        assert_eq!(
+            storage
+                .tokens_schema()
+                .get_nft_approved(TokenId(65536))
+                .await?,
+            Some(operator)
+        );
+    }
+    // Approvals are stored per token.
+    assert_eq!(
+        storage
+            .tokens_schema()
+            .get_nft_approved(TokenId(65537))
+            .await?,
+        None
+    );
+    Ok(())
+}
+
+/// Checks that minted NFTs are counted up to the requested block.
+#[db_test]
+async fn minted_nfts_count(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    for (serial_id, block_number) in [(0, 1), (1, 1), (2, 2)] {
+        let diff = StorageAccountDiff::MintNFT(StorageMintNFTUpdate {
+            token_id: MIN_NFT_TOKEN_ID as i32 + serial_id,
+            serial_id,
+            creator_account_id: 5,
+            creator_address: Address::default().as_bytes().to_vec(),
+            address: Address::random().as_bytes().to_vec(),
+            content_hash: H256::default().as_bytes().to_vec(),
+            update_order_id: serial_id,
+            block_number,
+            symbol: String::from("NFT"),
+            nonce: 0,
+        });
+        storage
+            .chain()
+            .state_schema()
+            .apply_storage_account_diff(diff)
+            .await?;
+    }
+
+    let mut tokens_schema = storage.tokens_schema();
+    assert_eq!(
+        tokens_schema.get_minted_nfts_count(BlockNumber(0)).await?,
+        0
+    );
+    assert_eq!(
+        tokens_schema.get_minted_nfts_count(BlockNumber(1)).await?,
+        2
+    );
+    assert_eq!(
+        tokens_schema.get_minted_nfts_count(BlockNumber(2)).await?,
+        3
+    );
+    Ok(())
+}
+
+/// Checks that minted NFTs are enumerated by their token id and that
+/// the indices of already minted NFTs don't change in later blocks.
+#[db_test]
+async fn minted_nft_by_index(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    for (serial_id, block_number) in [(0, 1), (1, 1), (2, 2)] {
+        let diff = StorageAccountDiff::MintNFT(StorageMintNFTUpdate {
+            token_id: MIN_NFT_TOKEN_ID as i32 + serial_id,
+            serial_id,
+            creator_account_id: 5,
+            creator_address: Address::default().as_bytes().to_vec(),
+            address: Address::random().as_bytes().to_vec(),
+            content_hash: H256::default().as_bytes().to_vec(),
+            update_order_id: serial_id,
+            block_number,
+            symbol: String::from("NFT"),
+            nonce: 0,
+        });
+        storage
+            .chain()
+            .state_schema()
+            .apply_storage_account_diff(diff)
+            .await?;
+    }
+
+    let mut tokens_schema = storage.tokens_schema();
+    for block_number in [BlockNumber(1), BlockNumber(2)] {
+        for index in 0..2 {
+            assert_eq!(
+                tokens_schema
+                    .get_minted_nft_by_index(block_number, index)
+                    .await?,
+                Some(TokenId(MIN_NFT_TOKEN_ID + index))
+            );
+        }
+    }
+    assert_eq!(
+        tokens_schema
+            .get_minted_nft_by_index(BlockNumber(1), 2)
+            .await?,
+        None
+    );
+    assert_eq!(
+        tokens_schema
+            .get_minted_nft_by_index(BlockNumber(2), 2)
+            .await?,
+        Some(TokenId(MIN_NFT_TOKEN_ID + 2))
+    );
+    assert_eq!(
+        tokens_schema
+            .get_minted_nft_by_index(BlockNumber(2), 3)
+            .await?,
+        None
+    );
+    Ok(())
+}
+
 /// Checks the store/load routine for `ticker_price` table.
 #[db_test]
 async fn test_ticker_price(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
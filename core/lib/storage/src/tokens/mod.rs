@@ -10,7 +10,7 @@ use zksync_api_types::v02::{
     pagination::{PaginationDirection, PaginationQuery},
     token::ApiNFT,
 };
-use zksync_types::{AccountId, Address, Token, TokenId, TokenLike, TokenPrice, NFT};
+use zksync_types::{AccountId, Address, BlockNumber, Token, TokenId, TokenLike, TokenPrice, NFT};
 use zksync_utils::ratio_to_big_decimal;
 // Local imports
 use self::records::{DBMarketVolume, DbTickerPrice, DbToken, StorageApiNFT, StorageNFT, TokenKind};
@@ -464,6 +464,127 @@ impl<'a, 'c> TokensSchema<'a, 'c> {
         Ok(db_token.map(|t| t.into()))
     }
 
+    /// Returns the human-readable name of the token (e.g. "USD Coin"), if it was stored.
+    pub async fn get_token_name(&mut self, token_id: TokenId) -> QueryResult<Option<String>> {
+        let start = Instant::now();
+        let name = sqlx::query!(
+            r#"
+            SELECT name FROM tokens
+            WHERE id = $1
+            "#,
+            *token_id as i32
+        )
+        .fetch_optional(self.0.conn())
+        .await?
+        .and_then(|record| record.name);
+
+        metrics::histogram!("sql.token.get_token_name", start.elapsed());
+        Ok(name)
+    }
+
+    /// Sets the human-readable name of the token.
+    pub async fn store_token_name(&mut self, token_id: TokenId, name: &str) -> QueryResult<()> {
+        let start = Instant::now();
+        sqlx::query!(
+            r#"
+            UPDATE tokens SET name = $1
+            WHERE id = $2
+            "#,
+            name,
+            *token_id as i32
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.token.store_token_name", start.elapsed());
+        Ok(())
+    }
+
+    /// Returns the operator approved to transfer the NFT, if any.
+    pub async fn get_nft_approved(&mut self, token_id: TokenId) -> QueryResult<Option<Address>> {
+        let start = Instant::now();
+        let operator = sqlx::query!(
+            r#"
+            SELECT operator FROM nft_approvals
+            WHERE token_id = $1
+            "#,
+            *token_id as i32
+        )
+        .fetch_optional(self.0.conn())
+        .await?
+        .map(|record| Address::from_slice(&record.operator));
+
+        metrics::histogram!("sql.token.get_nft_approved", start.elapsed());
+        Ok(operator)
+    }
+
+    /// Sets the operator approved to transfer the NFT, replacing the previous one.
+    pub async fn store_nft_approval(
+        &mut self,
+        token_id: TokenId,
+        operator: Address,
+    ) -> QueryResult<()> {
+        let start = Instant::now();
+        sqlx::query!(
+            r#"
+            INSERT INTO nft_approvals (token_id, operator)
+            VALUES ($1, $2)
+            ON CONFLICT (token_id) DO UPDATE SET operator = $2
+            "#,
+            *token_id as i32,
+            operator.as_bytes()
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.token.store_nft_approval", start.elapsed());
+        Ok(())
+    }
+
+    /// Returns the number of NFTs minted up to and including the given block.
+    pub async fn get_minted_nfts_count(&mut self, block_number: BlockNumber) -> QueryResult<u32> {
+        let start = Instant::now();
+        let count = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!" FROM mint_nft_updates
+            WHERE block_number <= $1
+            "#,
+            *block_number as i64
+        )
+        .fetch_one(self.0.conn())
+        .await?
+        .count;
+
+        metrics::histogram!("sql.token.get_minted_nfts_count", start.elapsed());
+        Ok(count as u32)
+    }
+
+    /// Returns the id of the NFT at the given index among the NFTs minted up to
+    /// and including the given block. NFTs are ordered by their token id.
+    pub async fn get_minted_nft_by_index(
+        &mut self,
+        block_number: BlockNumber,
+        index: u32,
+    ) -> QueryResult<Option<TokenId>> {
+        let start = Instant::now();
+        let token_id = sqlx::query!(
+            r#"
+            SELECT token_id FROM mint_nft_updates
+            WHERE block_number <= $1
+            ORDER BY token_id
+            OFFSET $2 LIMIT 1
+            "#,
+            *block_number as i64,
+            i64::from(index)
+        )
+        .fetch_optional(self.0.conn())
+        .await?
+        .map(|record| TokenId(record.token_id as u32));
+
+        metrics::histogram!("sql.token.get_minted_nft_by_index", start.elapsed());
+        Ok(token_id)
+    }
+
     pub async fn get_token_market_volume(
         &mut self,
         token_id: TokenId,